@@ -0,0 +1,121 @@
+# Deutsch
+
+menu-title = Tower Defense Tutorial
+menu-start = Spiel starten
+menu-quit = Beenden
+menu-editor = Level-Editor
+
+button-settings = Einstellungen
+button-controls = Steuerung
+button-restart = Neustart
+button-main-menu = Hauptmenü
+button-back = Zurück
+
+difficulty-easy = Leicht
+difficulty-normal = Normal
+difficulty-hard = Schwer
+difficulty-nightmare = Albtraum
+
+pause-title = Pausiert
+pause-resume = Weiter
+pause-quit = Zum Hauptmenü
+
+hud-money = Geld: { $money }
+hud-health = Leben: { $health }
+
+end-victory-title = Level geschafft { $stars }
+end-game-over-title = Spiel vorbei
+end-score = Punkte: { $score }
+end-lives-left = Verbleibende Leben: { $lives }
+end-cleared-in = Geschafft in { $seconds } Sekunden
+end-reached-wave = Welle { $wave } von { $waves } erreicht
+end-survived = { $seconds } Sekunden überlebt
+end-difficulty = Schwierigkeit: { $difficulty }
+end-kills = Zerstörte Gegner: { $kills }
+end-money-earned = Verdientes Geld: { $money }
+end-tower-summary = { $tower }: { $kills } Abschüsse, { $damage } Schaden, { $hits } von { $shots } Schüssen getroffen
+
+tower-tomato = Tomatenturm
+tower-potato = Kartoffelturm
+tower-cabbage = Kohlturm
+tower-shots = Schüsse: { $shots }
+tower-hits = Treffer: { $hits } ({ $rate } %)
+tower-damage = Verursachter Schaden: { $damage }
+tower-kills = Abschüsse: { $kills }
+tower-level = Stufe { $level } von { $max }
+tower-targeting-first = Ziel: Vorderster
+tower-targeting-last = Ziel: Hinterster
+tower-targeting-closest = Ziel: Nächster
+tower-targeting-strongest = Ziel: Stärkster
+tower-upgrade-cost = Verbesserung kostet: { $cost }
+tower-fully-upgraded = Voll verbessert
+tower-sell-value = Verkaufswert: { $money }
+tower-button-upgrade = Verbessern
+tower-button-targeting = Zielwahl
+tower-button-sell = Verkaufen
+
+high-score-title = Bestenliste
+high-score-enter-name = Neuer Rekord! Namen eingeben und Enter drücken: { $name }_
+high-score-entry = { $rank }. { $name }  { $score }
+
+settings-title = Einstellungen
+settings-master-volume = Gesamtlautstärke
+settings-sfx-volume = Effekte
+settings-music-volume = Musik
+settings-ui-volume = Oberfläche
+settings-window-mode = Fenstermodus
+settings-resolution = Auflösung
+settings-ui-scale = UI-Skalierung
+settings-language = Sprache
+
+window-windowed = Fenster
+window-borderless = Randlos
+window-fullscreen = Vollbild
+
+controls-title = Steuerung
+controls-reset = Standard wiederherstellen
+controls-press-key = Taste für diese Aktion drücken, Escape bricht ab
+controls-conflict = { $key } ist schon mit { $action } belegt
+controls-unbound = Nicht belegt
+
+action-build-tomato = Tomatenturm bauen
+action-build-potato = Kartoffelturm bauen
+action-build-cabbage = Kohlturm bauen
+action-sell = Turm verkaufen
+action-upgrade = Turm verbessern
+action-cycle-targeting = Zielwahl ändern
+action-next-wave = Nächste Welle rufen
+action-pause = Pause
+action-speed-normal = Normale Geschwindigkeit
+action-speed-double = Doppelte Geschwindigkeit
+action-speed-quadruple = Vierfache Geschwindigkeit
+action-toggle-flow-field = Flussfeld anzeigen
+action-playtest = Level testen
+action-pan-forward = Nach vorne schwenken
+action-pan-back = Nach hinten schwenken
+action-pan-left = Nach links schwenken
+action-pan-right = Nach rechts schwenken
+action-rotate-left = Nach links drehen
+action-rotate-right = Nach rechts drehen
+action-focus-selected = Auswahl zentrieren
+
+editor-title = Level-Editor
+editor-name = Name
+editor-floor-size = Bodengröße
+editor-wave-delay = Sekunden zwischen Wellen
+editor-tool-waypoints = Wegpunkte
+editor-tool-slots = Turmplätze
+editor-tool-spawn = Startpunkt
+editor-hint = Linksklick fügt hinzu oder verschiebt, Rechtsklick entfernt. F5 zum Testen.
+editor-waves = Wellen
+editor-wave-count = Gegner
+editor-wave-health = Leben
+editor-wave-speed = Tempo
+editor-wave-spacing = Abstand
+editor-add-wave = Welle hinzufügen
+editor-file = Datei
+editor-export = Exportieren
+editor-exported = Gespeichert in { $path }
+editor-playtest = Testen
+editor-needs-waypoint = Zuerst einen Wegpunkt für die Gegner hinzufügen
+editor-needs-wave = Zuerst eine Welle hinzufügen
@@ -0,0 +1,121 @@
+# English, also used for any message missing from another language
+
+menu-title = Tower Defense Tutorial
+menu-start = Start Game
+menu-quit = Quit
+menu-editor = Level Editor
+
+button-settings = Settings
+button-controls = Controls
+button-restart = Restart
+button-main-menu = Main Menu
+button-back = Back
+
+difficulty-easy = Easy
+difficulty-normal = Normal
+difficulty-hard = Hard
+difficulty-nightmare = Nightmare
+
+pause-title = Paused
+pause-resume = Resume
+pause-quit = Quit to Menu
+
+hud-money = Money: { $money }
+hud-health = Health: { $health }
+
+end-victory-title = Level Complete { $stars }
+end-game-over-title = Game Over
+end-score = Score: { $score }
+end-lives-left = Lives remaining: { $lives }
+end-cleared-in = Cleared in { $seconds } seconds
+end-reached-wave = Reached wave { $wave } of { $waves }
+end-survived = Survived { $seconds } seconds
+end-difficulty = Difficulty: { $difficulty }
+end-kills = Targets destroyed: { $kills }
+end-money-earned = Money earned: { $money }
+end-tower-summary = { $tower }: { $kills } kills, { $damage } damage, { $hits } of { $shots } shots hit
+
+tower-tomato = Tomato Tower
+tower-potato = Potato Tower
+tower-cabbage = Cabbage Tower
+tower-shots = Shots fired: { $shots }
+tower-hits = Hits: { $hits } ({ $rate }%)
+tower-damage = Damage dealt: { $damage }
+tower-kills = Kills: { $kills }
+tower-level = Level { $level } of { $max }
+tower-targeting-first = Targeting: First
+tower-targeting-last = Targeting: Last
+tower-targeting-closest = Targeting: Closest
+tower-targeting-strongest = Targeting: Strongest
+tower-upgrade-cost = Upgrade cost: { $cost }
+tower-fully-upgraded = Fully upgraded
+tower-sell-value = Sells for: { $money }
+tower-button-upgrade = Upgrade
+tower-button-targeting = Targeting
+tower-button-sell = Sell
+
+high-score-title = High Scores
+high-score-enter-name = New high score! Type your name and press Enter: { $name }_
+high-score-entry = { $rank }. { $name }  { $score }
+
+settings-title = Settings
+settings-master-volume = Master Volume
+settings-sfx-volume = Effects Volume
+settings-music-volume = Music Volume
+settings-ui-volume = Interface Volume
+settings-window-mode = Window Mode
+settings-resolution = Resolution
+settings-ui-scale = UI Scale
+settings-language = Language
+
+window-windowed = Windowed
+window-borderless = Borderless
+window-fullscreen = Fullscreen
+
+controls-title = Controls
+controls-reset = Reset to Defaults
+controls-press-key = Press a key for this action, or Escape to cancel
+controls-conflict = { $key } is already used for { $action }
+controls-unbound = Unbound
+
+action-build-tomato = Build Tomato Tower
+action-build-potato = Build Potato Tower
+action-build-cabbage = Build Cabbage Tower
+action-sell = Sell Tower
+action-upgrade = Upgrade Tower
+action-cycle-targeting = Change Targeting
+action-next-wave = Call Next Wave
+action-pause = Pause
+action-speed-normal = Normal Speed
+action-speed-double = Double Speed
+action-speed-quadruple = Quadruple Speed
+action-toggle-flow-field = Show Flow Field
+action-playtest = Playtest Level
+action-pan-forward = Pan Forward
+action-pan-back = Pan Back
+action-pan-left = Pan Left
+action-pan-right = Pan Right
+action-rotate-left = Rotate Left
+action-rotate-right = Rotate Right
+action-focus-selected = Focus Selected
+
+editor-title = Level Editor
+editor-name = Name
+editor-floor-size = Floor size
+editor-wave-delay = Seconds between waves
+editor-tool-waypoints = Waypoints
+editor-tool-slots = Tower slots
+editor-tool-spawn = Spawn
+editor-hint = Left click adds or drags, right click removes. F5 playtests.
+editor-waves = Waves
+editor-wave-count = Targets
+editor-wave-health = Health
+editor-wave-speed = Speed
+editor-wave-spacing = Spacing
+editor-add-wave = Add Wave
+editor-file = File
+editor-export = Export
+editor-exported = Saved to { $path }
+editor-playtest = Playtest
+editor-needs-waypoint = Add a waypoint for the targets to walk to first
+editor-needs-wave = Add a wave first
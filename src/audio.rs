@@ -0,0 +1,250 @@
+use std::{fs, path::Path};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::components::{
+    NotEnoughMoneyEvent, TargetDeathEvent, TargetLeakedEvent, TowerFiredEvent, WaveStartedEvent,
+};
+use crate::settings::Settings;
+
+/// Maps game events to sound files, inside the assets folder
+pub const SOUND_CATALOGUE_FILE: &str = "audio/catalogue.sounds.ron";
+
+/// Something that happened in the game that can have a sound
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoundCue {
+    TowerFired,
+    TargetKilled,
+    TargetLeaked,
+    WaveStarted,
+    NotEnoughMoney,
+    ButtonClicked,
+}
+
+/// Each channel has its own volume in the settings, on top of the master volume
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioChannel {
+    Sfx,
+    Music,
+    Ui,
+}
+
+impl AudioChannel {
+    pub fn volume(&self, settings: &Settings) -> f32 {
+        match self {
+            AudioChannel::Sfx => settings.sfx_volume(),
+            AudioChannel::Music => settings.music_volume(),
+            AudioChannel::Ui => settings.ui_volume(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SoundEntry {
+    /// Path inside the assets folder
+    pub file: String,
+    pub channel: AudioChannel,
+}
+
+/// The sound data file, cues missing from it are silent
+#[derive(Serialize, Deserialize, TypeUuid, Clone, Debug, Default)]
+#[uuid = "5d0b6c2e-8f43-4a57-9a1e-3c7b2f0e9d14"]
+#[serde(default)]
+pub struct SoundCatalogue {
+    pub cues: HashMap<SoundCue, SoundEntry>,
+    /// Looped on the music channel
+    pub music: Option<String>,
+}
+
+impl SoundCatalogue {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+        ron::from_str(&contents)
+            .map_err(|err| format!("failed to parse {}: {}", path.display(), err))
+    }
+}
+
+/// Reads `.sounds.ron` files for the asset server
+#[derive(Default)]
+struct SoundCatalogueLoader;
+
+impl AssetLoader for SoundCatalogueLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let catalogue: SoundCatalogue = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(catalogue));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sounds.ron"]
+    }
+}
+
+/// Kept so the catalogue stays loaded
+#[derive(Resource)]
+struct SoundCatalogueHandle(Handle<SoundCatalogue>);
+
+/// Every sound in the catalogue, loaded with it so nothing is loaded mid-match
+#[derive(Resource, Default)]
+pub struct SoundHandles {
+    pub cues: HashMap<SoundCue, (Handle<AudioSource>, AudioChannel)>,
+    pub music: Option<Handle<AudioSource>>,
+}
+
+/// Plays a cue, asking for the same cue several times in one frame only plays it once
+pub struct PlaySoundEvent(pub SoundCue);
+
+#[derive(Resource)]
+struct MusicSink(Handle<AudioSink>);
+
+pub struct AudioManagerPlugin;
+
+impl Plugin for AudioManagerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlaySoundEvent>()
+            .add_asset::<SoundCatalogue>()
+            .init_asset_loader::<SoundCatalogueLoader>()
+            .init_resource::<SoundHandles>()
+            .add_startup_system(load_sound_catalogue)
+            .add_system(load_sounds)
+            // Like the stats, this catches the events sent on the frame a state changes
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_system(cue_game_events)
+                    .with_system(cue_button_clicks)
+                    .with_system(play_sounds.after(cue_game_events).after(cue_button_clicks))
+                    .with_system(update_music_volume),
+            );
+    }
+}
+
+fn load_sound_catalogue(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundCatalogueHandle(
+        asset_server.load(SOUND_CATALOGUE_FILE),
+    ));
+}
+
+/// Loads the sounds once the catalogue is in, and starts the music where there is audio output
+fn load_sounds(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<SoundCatalogue>>,
+    catalogues: Res<Assets<SoundCatalogue>>,
+    asset_server: Res<AssetServer>,
+    audio: Option<Res<Audio>>,
+    settings: Res<Settings>,
+) {
+    for event in events.iter() {
+        let AssetEvent::Created { handle } = event else {
+            continue;
+        };
+        let Some(catalogue) = catalogues.get(handle) else {
+            continue;
+        };
+
+        let handles = SoundHandles {
+            cues: catalogue
+                .cues
+                .iter()
+                .map(|(cue, entry)| (*cue, (asset_server.load(&entry.file), entry.channel)))
+                .collect(),
+            music: catalogue.music.as_ref().map(|file| asset_server.load(file)),
+        };
+
+        if let (Some(music), Some(audio)) = (&handles.music, &audio) {
+            let sink = audio.play_with_settings(
+                music.clone(),
+                PlaybackSettings::LOOP.with_volume(AudioChannel::Music.volume(&settings)),
+            );
+            commands.insert_resource(MusicSink(sink));
+        }
+
+        commands.insert_resource(handles);
+    }
+}
+
+fn cue_game_events(
+    mut fired_events: EventReader<TowerFiredEvent>,
+    mut death_events: EventReader<TargetDeathEvent>,
+    mut leak_events: EventReader<TargetLeakedEvent>,
+    mut wave_events: EventReader<WaveStartedEvent>,
+    mut money_events: EventReader<NotEnoughMoneyEvent>,
+    mut sounds: EventWriter<PlaySoundEvent>,
+) {
+    let cues = fired_events
+        .iter()
+        .map(|_| SoundCue::TowerFired)
+        .chain(death_events.iter().map(|_| SoundCue::TargetKilled))
+        .chain(leak_events.iter().map(|_| SoundCue::TargetLeaked))
+        .chain(wave_events.iter().map(|_| SoundCue::WaveStarted))
+        .chain(money_events.iter().map(|_| SoundCue::NotEnoughMoney));
+
+    for cue in cues {
+        sounds.send(PlaySoundEvent(cue));
+    }
+}
+
+fn cue_button_clicks(
+    interactions: Query<&Interaction, (With<Button>, Changed<Interaction>)>,
+    mut sounds: EventWriter<PlaySoundEvent>,
+) {
+    if interactions
+        .iter()
+        .any(|interaction| matches!(interaction, Interaction::Clicked))
+    {
+        sounds.send(PlaySoundEvent(SoundCue::ButtonClicked));
+    }
+}
+
+fn play_sounds(
+    mut events: EventReader<PlaySoundEvent>,
+    handles: Res<SoundHandles>,
+    audio: Option<Res<Audio>>,
+    settings: Res<Settings>,
+) {
+    let cues: HashSet<SoundCue> = events.iter().map(|event| event.0).collect();
+    // Headless apps have no audio output, the cues are still sent for tests to check
+    let Some(audio) = audio else {
+        return;
+    };
+
+    for cue in cues {
+        let Some((sound, channel)) = handles.cues.get(&cue) else {
+            continue;
+        };
+        audio.play_with_settings(
+            sound.clone(),
+            PlaybackSettings::ONCE.with_volume(channel.volume(&settings)),
+        );
+    }
+}
+
+fn update_music_volume(
+    settings: Res<Settings>,
+    music: Option<Res<MusicSink>>,
+    sinks: Option<Res<Assets<AudioSink>>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let (Some(music), Some(sinks)) = (music, sinks) {
+        if let Some(sink) = sinks.get(&music.0) {
+            sink.set_volume(AudioChannel::Music.volume(&settings));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Plays every combination of tower layout and wave set from a matrix file without a window,
+//! and writes one CSV row of statistics per match.
+//!
+//! ```text
+//! cargo run --release --bin balance -- balance/matrix.ron --output report.csv
+//! ```
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    process,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use towerdefense::{
+    components::TowerType,
+    headless::{headless_app, run_simulation, SimulationReport},
+    Level, TowerLayout, Wave,
+};
+
+const USAGE: &str = "usage: balance <matrix.ron> [--output <report.csv>]";
+
+#[derive(Deserialize)]
+struct BalanceMatrix {
+    /// Level to play, the built in tutorial when missing
+    #[serde(default)]
+    level: Option<String>,
+    /// Seconds of game time before a match counts as timed out
+    time_limit: f32,
+    layouts: Vec<NamedLayout>,
+    wave_sets: Vec<NamedWaveSet>,
+}
+
+#[derive(Deserialize)]
+struct NamedLayout {
+    name: String,
+    /// Same `slot=type` format as the headless binary
+    towers: String,
+}
+
+#[derive(Deserialize)]
+struct NamedWaveSet {
+    name: String,
+    waves: Vec<Wave>,
+}
+
+fn main() {
+    let mut matrix_path = None;
+    let mut output_path = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output_path = Some(args.next().unwrap_or_else(|| fail("missing output"))),
+            _ if matrix_path.is_none() => matrix_path = Some(arg),
+            _ => fail(&format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let matrix_path = matrix_path.unwrap_or_else(|| fail("missing matrix file"));
+    let matrix: BalanceMatrix = fs::read_to_string(&matrix_path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| fail(&format!("failed to load {}: {}", matrix_path, err)));
+
+    let level = match &matrix.level {
+        Some(path) => Level::load(path).unwrap_or_else(|err| fail(&err)),
+        None => Level::default(),
+    };
+    let time_limit = Duration::from_secs_f32(matrix.time_limit);
+
+    let mut csv = header();
+    for named_layout in &matrix.layouts {
+        let layout: TowerLayout = named_layout
+            .towers
+            .parse()
+            .unwrap_or_else(|err: String| fail(&format!("layout {}: {}", named_layout.name, err)));
+
+        for wave_set in &matrix.wave_sets {
+            let mut level = level.clone();
+            level.waves = wave_set.waves.clone();
+
+            let mut app = headless_app(level, layout.clone());
+            let report = run_simulation(&mut app, time_limit);
+            eprintln!(
+                "{} / {}: {}",
+                named_layout.name, wave_set.name, report.result
+            );
+
+            csv.push_str(&row(&named_layout.name, &wave_set.name, &report));
+        }
+    }
+
+    let written = match output_path {
+        Some(path) => fs::write(&path, csv),
+        None => io::stdout().write_all(csv.as_bytes()),
+    };
+    if let Err(err) = written {
+        fail(&format!("failed to write report: {}", err));
+    }
+}
+
+fn header() -> String {
+    let mut columns = vec![
+        "layout",
+        "wave_set",
+        "result",
+        "duration",
+        "lives_lost",
+        "money_earned",
+        "kills",
+        "shots_fired",
+        "hit_rate",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect::<Vec<_>>();
+
+    for tower_type in TowerType::ALL {
+        let prefix = format!("{:?}", tower_type).to_lowercase();
+        for stat in ["shots", "hits", "hit_rate", "damage", "kills"] {
+            columns.push(format!("{}_{}", prefix, stat));
+        }
+    }
+
+    columns.join(",") + "\n"
+}
+
+fn row(layout: &str, wave_set: &str, report: &SimulationReport) -> String {
+    let total = report.stats.total();
+    let mut fields = vec![
+        escape(layout),
+        escape(wave_set),
+        report.result.to_string(),
+        format!("{:.2}", report.duration.as_secs_f32()),
+        report.stats.lives_lost.to_string(),
+        report.stats.money_earned.to_string(),
+        report.stats.kills.to_string(),
+        total.shots.to_string(),
+        format!("{:.3}", total.hit_rate()),
+    ];
+
+    for tower_type in TowerType::ALL {
+        let stats = report.stats.tower_type(tower_type);
+        fields.push(stats.shots.to_string());
+        fields.push(stats.hits.to_string());
+        fields.push(format!("{:.3}", stats.hit_rate()));
+        fields.push(stats.damage.to_string());
+        fields.push(stats.kills.to_string());
+    }
+
+    fields.join(",") + "\n"
+}
+
+/// Quotes a field when it would otherwise break the CSV
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2);
+}
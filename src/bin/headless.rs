@@ -0,0 +1,52 @@
+//! Plays a single match without a window and prints how it went.
+//!
+//! ```text
+//! cargo run --release --bin headless -- --level assets/levels/tutorial.ron --towers 0=tomato,3=cabbage --difficulty hard
+//! ```
+
+use std::{env, process, time::Duration};
+
+use towerdefense::{
+    headless::{headless_app, run_simulation},
+    Difficulty, Level, TowerLayout,
+};
+
+const USAGE: &str =
+    "usage: headless [--level <file.ron>] [--towers <slot=type,...>] [--difficulty <name>] [--time-limit <seconds>]";
+
+fn main() {
+    let mut level = Level::default();
+    let mut layout = TowerLayout::default();
+    let mut difficulty = Difficulty::default();
+    let mut time_limit = Duration::from_secs(600);
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .unwrap_or_else(|| fail(&format!("missing value for {}", arg)));
+        let parsed = match arg.as_str() {
+            "--level" => Level::load(&value).map(|loaded| level = loaded),
+            "--towers" => value.parse().map(|parsed| layout = parsed),
+            "--difficulty" => value.parse().map(|parsed| difficulty = parsed),
+            "--time-limit" => value
+                .parse()
+                .map(|seconds| time_limit = Duration::from_secs_f32(seconds))
+                .map_err(|_| format!("invalid time limit '{}'", value)),
+            _ => Err(format!("unknown argument {}", arg)),
+        };
+        if let Err(err) = parsed {
+            fail(&err);
+        }
+    }
+
+    let mut app = headless_app(level, layout);
+    app.insert_resource(difficulty);
+    let report = run_simulation(&mut app, time_limit);
+    println!("{}", report);
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2);
+}
@@ -0,0 +1,341 @@
+use bevy::{math::Vec3Swizzles, prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::RtsCamera;
+use crate::components::{GameAssets, GameState, NotEnoughMoneyEvent, Target, TowerType};
+use crate::level::Level;
+use crate::pathfinding::FlowField;
+use crate::player::Player;
+use crate::state_scoped::StateScoped;
+use crate::tower::{spawn_tower, TowerPickingAssets, TOWER_BASE_HEIGHT};
+
+/// Divides the floor into square cells, so towers can be built anywhere away from the path
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BuildGrid {
+    pub cell_size: f32,
+    /// Cells with their centre closer than this to the path are kept clear for the targets
+    pub path_clearance: f32,
+    /// Targets find their own way to the exit around the towers instead of following the
+    /// waypoints, towers can go anywhere that leaves them a way through
+    #[serde(default)]
+    pub mazing: bool,
+}
+
+impl Default for BuildGrid {
+    fn default() -> Self {
+        Self {
+            cell_size: 1.0,
+            path_clearance: 1.0,
+            mazing: false,
+        }
+    }
+}
+
+impl BuildGrid {
+    pub fn cells_per_side(&self, floor_size: f32) -> i32 {
+        (floor_size / self.cell_size).floor() as i32
+    }
+
+    /// The cell under a ground position, `None` off the floor
+    pub fn cell_at(&self, position: Vec2, floor_size: f32) -> Option<IVec2> {
+        let cell = ((position + floor_size / 2.0) / self.cell_size)
+            .floor()
+            .as_ivec2();
+        let cells = self.cells_per_side(floor_size);
+        (cell.cmpge(IVec2::ZERO).all() && cell.cmplt(IVec2::splat(cells)).all()).then_some(cell)
+    }
+
+    pub fn cell_center(&self, cell: IVec2, floor_size: f32) -> Vec2 {
+        (cell.as_vec2() + 0.5) * self.cell_size - floor_size / 2.0
+    }
+}
+
+/// Why a tower can't go where the cursor is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlacementError {
+    /// The level has no build grid, towers only go on its slots
+    NoGrid,
+    OutOfBounds,
+    OnPath,
+    Occupied,
+    /// In mazing mode, the tower would leave the spawn or a live target no way to the exit,
+    /// or it would stand on a target
+    BlocksPath,
+}
+
+/// The cell a tower at `position` would be built on, if one can be built there.
+/// `targets` are the ground positions of the live targets, only mazing levels look at them.
+pub fn check_placement(
+    level: &Level,
+    position: Vec2,
+    occupied: &HashSet<IVec2>,
+    targets: &[Vec2],
+) -> Result<IVec2, PlacementError> {
+    let grid = level.build_grid.as_ref().ok_or(PlacementError::NoGrid)?;
+    let cell = grid
+        .cell_at(position, level.floor_size)
+        .ok_or(PlacementError::OutOfBounds)?;
+
+    if occupied.contains(&cell) {
+        return Err(PlacementError::Occupied);
+    }
+    if grid.mazing {
+        let mut blocked = occupied.clone();
+        blocked.insert(cell);
+        let field = FlowField::compute(level, &blocked);
+        let spawn_cell = grid
+            .cell_at(level.spawn, level.floor_size)
+            .ok_or(PlacementError::BlocksPath)?;
+        // Targets already on their way need a route from where they are, not just the spawn.
+        // Those lined up behind the spawn are off the grid and follow the spawn's route.
+        let cut_off = targets
+            .iter()
+            .filter_map(|position| grid.cell_at(*position, level.floor_size))
+            .chain([spawn_cell])
+            .any(|from| from == cell || field.distance(from).is_none());
+        return if cut_off {
+            Err(PlacementError::BlocksPath)
+        } else {
+            Ok(cell)
+        };
+    }
+    if level.distance_to_path(grid.cell_center(cell, level.floor_size)) < grid.path_clearance {
+        return Err(PlacementError::OnPath);
+    }
+    Ok(cell)
+}
+
+/// Cells towers can't be placed on: those with a grid tower on them, and those holding one of
+/// the level's tower slots whether it's built on or not
+pub fn occupied_cells<'a>(
+    level: &Level,
+    towers: impl IntoIterator<Item = &'a GridCell>,
+) -> HashSet<IVec2> {
+    let mut occupied: HashSet<IVec2> = towers.into_iter().map(|cell| cell.0).collect();
+    if let Some(grid) = &level.build_grid {
+        occupied.extend(
+            level
+                .tower_slots
+                .iter()
+                .filter_map(|slot| grid.cell_at(*slot, level.floor_size)),
+        );
+    }
+    occupied
+}
+
+fn target_positions(targets: &Query<&Transform, With<Target>>) -> Vec<Vec2> {
+    targets
+        .iter()
+        .map(|transform| transform.translation.xz())
+        .collect()
+}
+
+/// The build grid cell a tower stands on
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridCell(pub IVec2);
+
+/// Where the cursor points at on the ground, `None` while it's off the window
+#[derive(Resource, Default)]
+pub struct CursorGroundPosition(pub Option<Vec2>);
+
+/// The tower type following the cursor, waiting to be placed
+#[derive(Resource, Default)]
+pub struct Placement {
+    pub tower_type: Option<TowerType>,
+}
+
+/// Preview of the tower being placed, snapped to the grid and tinted by whether it can go there
+#[derive(Component)]
+pub struct PlacementGhost;
+
+#[derive(Resource)]
+pub struct PlacementAssets {
+    pub mesh: Handle<Mesh>,
+    pub valid_color: Handle<StandardMaterial>,
+    pub invalid_color: Handle<StandardMaterial>,
+}
+
+impl FromWorld for PlacementAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(shape::Capsule::default().into());
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            mesh,
+            valid_color: materials.add(Color::rgba(0.2, 0.9, 0.2, 0.5).into()),
+            invalid_color: materials.add(Color::rgba(0.9, 0.2, 0.2, 0.5).into()),
+        }
+    }
+}
+
+pub struct BuildGridPlugin;
+
+impl Plugin for BuildGridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CursorGroundPosition>()
+            .init_resource::<Placement>()
+            .init_resource::<PlacementAssets>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .with_system(spawn_placement_ghost)
+                    .with_system(cancel_placement),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(track_cursor)
+                    .with_system(place_tower.after(track_cursor))
+                    .with_system(update_placement_ghost.after(place_tower)),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Editor).with_system(track_cursor));
+    }
+}
+
+fn cancel_placement(mut placement: ResMut<Placement>) {
+    placement.tower_type = None;
+}
+
+fn spawn_placement_ghost(mut commands: Commands, assets: Res<PlacementAssets>) {
+    commands
+        .spawn(PbrBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.valid_color.clone(),
+            visibility: Visibility::INVISIBLE,
+            ..default()
+        })
+        .insert(PlacementGhost)
+        .insert(Name::new("Placement_Ghost"))
+        .insert(StateScoped(GameState::InGame));
+}
+
+/// Casts the cursor onto the floor through the main camera
+fn track_cursor(
+    windows: Option<Res<Windows>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<RtsCamera>>,
+    mut cursor: ResMut<CursorGroundPosition>,
+) {
+    // Without a window, e.g. in tests, whatever was set stays
+    let Some(window) = windows.as_ref().and_then(|windows| windows.get_primary()) else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+
+    let ray = window
+        .cursor_position()
+        .and_then(|position| camera.viewport_to_world(camera_transform, position));
+    cursor.0 = ray.and_then(|ray| {
+        // Where the ray crosses the floor plane, if it points down at all
+        if ray.direction.y >= 0.0 {
+            return None;
+        }
+        let distance = -ray.origin.y / ray.direction.y;
+        Some((ray.origin + ray.direction * distance).xz())
+    });
+}
+
+fn place_tower(
+    mut commands: Commands,
+    mouse: Res<Input<MouseButton>>,
+    cursor: Res<CursorGroundPosition>,
+    mut placement: ResMut<Placement>,
+    ui: Query<&Interaction, With<Node>>,
+    cells: Query<&GridCell>,
+    targets: Query<&Transform, With<Target>>,
+    mut player: Query<&mut Player>,
+    level: Res<Level>,
+    assets: Res<GameAssets>,
+    picking: Res<TowerPickingAssets>,
+    mut money_events: EventWriter<NotEnoughMoneyEvent>,
+) {
+    let Some(tower_type) = placement.tower_type else {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Right) {
+        placement.tower_type = None;
+        return;
+    }
+
+    // Clicks on the HUD, including the one on the build button, aren't meant for the floor
+    let over_ui = ui
+        .iter()
+        .any(|interaction| !matches!(interaction, Interaction::None));
+    if !mouse.just_pressed(MouseButton::Left) || over_ui {
+        return;
+    }
+
+    let Some(position) = cursor.0 else {
+        return;
+    };
+    let occupied = occupied_cells(&level, &cells);
+    let targets = target_positions(&targets);
+    let Ok(cell) = check_placement(&level, position, &occupied, &targets) else {
+        return;
+    };
+
+    let mut player = player.single_mut();
+    if player.money < tower_type.cost() {
+        money_events.send(NotEnoughMoneyEvent);
+        return;
+    }
+
+    player.money -= tower_type.cost();
+    let grid = level.build_grid.as_ref().unwrap();
+    let translation = grid
+        .cell_center(cell, level.floor_size)
+        .extend(TOWER_BASE_HEIGHT)
+        .xzy();
+    let tower = spawn_tower(&mut commands, &assets, &picking, translation, tower_type);
+    commands.entity(tower).insert(GridCell(cell));
+    placement.tower_type = None;
+}
+
+fn update_placement_ghost(
+    placement: Res<Placement>,
+    cursor: Res<CursorGroundPosition>,
+    cells: Query<&GridCell>,
+    targets: Query<&Transform, With<Target>>,
+    level: Res<Level>,
+    assets: Res<PlacementAssets>,
+    mut ghosts: Query<
+        (
+            &mut Transform,
+            &mut Visibility,
+            &mut Handle<StandardMaterial>,
+        ),
+        With<PlacementGhost>,
+    >,
+) {
+    let Ok((mut transform, mut visibility, mut material)) = ghosts.get_single_mut() else {
+        return;
+    };
+
+    let (Some(grid), Some(_), Some(position)) =
+        (level.build_grid.as_ref(), placement.tower_type, cursor.0)
+    else {
+        visibility.is_visible = false;
+        return;
+    };
+
+    let Some(cell) = grid.cell_at(position, level.floor_size) else {
+        visibility.is_visible = false;
+        return;
+    };
+
+    let occupied = occupied_cells(&level, &cells);
+    let targets = target_positions(&targets);
+    let valid = check_placement(&level, position, &occupied, &targets).is_ok();
+
+    visibility.is_visible = true;
+    transform.translation = grid
+        .cell_center(cell, level.floor_size)
+        .extend(TOWER_BASE_HEIGHT)
+        .xzy();
+    *material = if valid {
+        assets.valid_color.clone()
+    } else {
+        assets.invalid_color.clone()
+    };
+}
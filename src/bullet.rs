@@ -1,17 +1,42 @@
 use bevy::prelude::*;
 
-use crate::components::{Bullet, GameState, Lifetime};
+use crate::components::{Bullet, GameState, Lifetime, Target};
+use crate::game_speed::GameTime;
 
-fn move_bullets(mut bullets: Query<(&Bullet, &mut Transform)>, time: Res<Time>) {
+/// Furthest a bullet moves between two checks for a target in its way, well under the width of
+/// a bullet and a target together so sped up bullets can't skip over one
+const MAX_BULLET_STEP: f32 = 0.2;
+/// A bullet touches a target when their centres are closer than this on every axis
+const BULLET_REACH: f32 = 0.2;
+
+fn move_bullets(
+    mut bullets: Query<(&Bullet, &mut Transform)>,
+    targets: Query<&GlobalTransform, With<Target>>,
+    time: GameTime,
+) {
     for (bullet, mut transform) in &mut bullets {
-        transform.translation += bullet.direction.normalize() * bullet.speed * time.delta_seconds();
+        let travel = bullet.direction.normalize() * bullet.speed * time.delta_seconds();
+        // Stop where a target is first touched, the physics step then reports the hit
+        let steps = (travel.length() / MAX_BULLET_STEP).ceil().max(1.0);
+        for _ in 0..steps as usize {
+            transform.translation += travel / steps;
+            let touching = targets.iter().any(|target| {
+                (target.translation() - transform.translation)
+                    .abs()
+                    .cmplt(Vec3::splat(BULLET_REACH))
+                    .all()
+            });
+            if touching {
+                break;
+            }
+        }
     }
 }
 
 fn bullet_despawn(
     mut commands: Commands,
     mut bullets: Query<(Entity, &mut Lifetime)>,
-    time: Res<Time>,
+    time: GameTime,
 ) {
     for (entity, mut lifetime) in &mut bullets {
         lifetime.timer.tick(time.delta());
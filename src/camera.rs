@@ -0,0 +1,289 @@
+use std::f32::consts::FRAC_PI_4;
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    math::Vec3Swizzles,
+    prelude::*,
+};
+use bevy_egui::EguiContext;
+use bevy_mod_picking::Selection;
+
+use crate::components::GameState;
+use crate::controls::{Controls, GameAction};
+use crate::editor::EditedLevel;
+use crate::level::Level;
+
+/// Lowest height above the ground the camera can zoom in to
+pub const MIN_ZOOM: f32 = 4.0;
+pub const MAX_ZOOM: f32 = 24.0;
+/// Angle turned by each press of a rotate key
+pub const ROTATION_SNAP: f32 = FRAC_PI_4;
+
+/// Ground distance panned per second, per unit of zoom, so panning feels the same at any height
+const PAN_SPEED: f32 = 1.0;
+/// Pixels from the window border where the cursor starts panning
+const EDGE_SCROLL_MARGIN: f32 = 16.0;
+/// Zoom change per line of the scroll wheel
+const ZOOM_STEP: f32 = 1.0;
+/// Touchpads scroll in pixels, this many make up a line
+const PIXELS_PER_LINE: f32 = 50.0;
+/// Horizontal distance behind the focus per unit of height, sets the viewing angle
+const BACK_OFFSET: f32 = 0.75;
+/// How quickly the view turns to the snapped angle, per second
+const ROTATION_SMOOTHING: f32 = 10.0;
+
+/// A top-down strategy camera looking at a point on the ground
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct RtsCamera {
+    /// Ground point in the middle of the view, `Vec2::y` is world z
+    pub focus: Vec2,
+    /// Height above the ground, kept between `MIN_ZOOM` and `MAX_ZOOM`
+    pub zoom: f32,
+    /// Rotation snaps turned from the starting view, positive is clockwise from above
+    pub rotation: i32,
+    /// Angle shown right now, eased towards the snapped one
+    yaw: f32,
+}
+
+impl Default for RtsCamera {
+    fn default() -> Self {
+        Self {
+            focus: Vec2::ZERO,
+            zoom: 10.0,
+            rotation: 0,
+            yaw: 0.0,
+        }
+    }
+}
+
+impl RtsCamera {
+    /// The angle the view is turning to
+    pub fn target_yaw(&self) -> f32 {
+        self.rotation as f32 * -ROTATION_SNAP
+    }
+
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    /// Ground direction the view looks along
+    pub fn forward(&self) -> Vec2 {
+        (Quat::from_rotation_y(self.yaw) * Vec3::Z).xz()
+    }
+
+    pub fn right(&self) -> Vec2 {
+        (Quat::from_rotation_y(self.yaw) * Vec3::NEG_X).xz()
+    }
+
+    /// Keeps the focus over the level's floor and the zoom in range
+    pub fn clamp_to(&mut self, level: &Level) {
+        let half_size = Vec2::splat(level.floor_size / 2.0);
+        self.focus = self.focus.clamp(-half_size, half_size);
+        self.zoom = self.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    pub fn transform(&self) -> Transform {
+        let focus = self.focus.extend(0.0).xzy();
+        let offset =
+            Quat::from_rotation_y(self.yaw) * Vec3::new(0.0, self.zoom, -self.zoom * BACK_OFFSET);
+        Transform::from_translation(focus + offset).looking_at(focus, Vec3::Y)
+    }
+}
+
+pub struct RtsCameraPlugin;
+
+impl Plugin for RtsCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<RtsCamera>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(reset_camera))
+            .add_system_set(SystemSet::on_enter(GameState::Editor).with_system(reset_camera))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(pan_camera)
+                    .with_system(zoom_camera)
+                    .with_system(rotate_camera)
+                    .with_system(focus_selected)
+                    .with_system(
+                        update_camera_transform
+                            .after(pan_camera)
+                            .after(zoom_camera)
+                            .after(rotate_camera)
+                            .after(focus_selected),
+                    ),
+            )
+            // The editor moves around the level the same way, there's nothing to select
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor)
+                    .with_system(pan_camera)
+                    .with_system(zoom_camera)
+                    .with_system(rotate_camera)
+                    .with_system(
+                        update_editor_camera_transform
+                            .after(pan_camera)
+                            .after(zoom_camera)
+                            .after(rotate_camera),
+                    ),
+            );
+    }
+}
+
+// Every level starts looking at the middle of the floor
+fn reset_camera(mut cameras: Query<&mut RtsCamera>) {
+    for mut camera in &mut cameras {
+        *camera = RtsCamera::default();
+    }
+}
+
+/// Keys typed into an egui field, like the level editor's name, aren't meant for the camera
+fn typing_into_egui(egui: Option<ResMut<EguiContext>>) -> bool {
+    egui.map_or(false, |mut egui| egui.ctx_mut().wants_keyboard_input())
+}
+
+fn pan_camera(
+    controls: Controls,
+    egui: Option<ResMut<EguiContext>>,
+    windows: Option<Res<Windows>>,
+    time: Res<Time>,
+    mut cameras: Query<&mut RtsCamera>,
+) {
+    let mut direction = Vec2::ZERO;
+    let keys = [
+        (GameAction::PanForward, Vec2::Y),
+        (GameAction::PanBack, Vec2::NEG_Y),
+        (GameAction::PanLeft, Vec2::NEG_X),
+        (GameAction::PanRight, Vec2::X),
+    ];
+    let typing = typing_into_egui(egui);
+    for (action, step) in keys {
+        if !typing && controls.pressed(action) {
+            direction += step;
+        }
+    }
+
+    if let Some(window) = windows.as_ref().and_then(|windows| windows.get_primary()) {
+        direction += edge_scroll(window);
+    }
+
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    let direction = direction.clamp_length_max(1.0);
+    for mut camera in &mut cameras {
+        let pan = camera.right() * direction.x + camera.forward() * direction.y;
+        camera.focus += pan * PAN_SPEED * camera.zoom * time.delta_seconds();
+    }
+}
+
+/// Direction to pan while the cursor rests against a window border
+fn edge_scroll(window: &Window) -> Vec2 {
+    let Some(cursor) = window.cursor_position() else {
+        return Vec2::ZERO;
+    };
+
+    // The cursor position starts from the bottom left corner
+    let axis = |position: f32, size: f32| {
+        if position < EDGE_SCROLL_MARGIN {
+            -1.0
+        } else if position > size - EDGE_SCROLL_MARGIN {
+            1.0
+        } else {
+            0.0
+        }
+    };
+    Vec2::new(
+        axis(cursor.x, window.width()),
+        axis(cursor.y, window.height()),
+    )
+}
+
+fn zoom_camera(mut wheel: EventReader<MouseWheel>, mut cameras: Query<&mut RtsCamera>) {
+    let lines: f32 = wheel
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_LINE,
+        })
+        .sum();
+
+    if lines == 0.0 {
+        return;
+    }
+
+    for mut camera in &mut cameras {
+        camera.zoom = (camera.zoom - lines * ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+fn rotate_camera(
+    controls: Controls,
+    egui: Option<ResMut<EguiContext>>,
+    time: Res<Time>,
+    mut cameras: Query<&mut RtsCamera>,
+) {
+    let mut turn = 0;
+    if !typing_into_egui(egui) {
+        if controls.just_pressed(GameAction::RotateLeft) {
+            turn -= 1;
+        }
+        if controls.just_pressed(GameAction::RotateRight) {
+            turn += 1;
+        }
+    }
+
+    let smoothing = (ROTATION_SMOOTHING * time.delta_seconds()).min(1.0);
+    for mut camera in &mut cameras {
+        if turn == 0 && camera.yaw == camera.target_yaw() {
+            continue;
+        }
+
+        camera.rotation += turn;
+        let remaining = camera.target_yaw() - camera.yaw;
+        // Lands exactly on the snapped angle instead of easing forever
+        camera.yaw = if remaining.abs() < 0.001 {
+            camera.target_yaw()
+        } else {
+            camera.yaw + remaining * smoothing
+        };
+    }
+}
+
+fn focus_selected(
+    controls: Controls,
+    selected: Query<(&Selection, &GlobalTransform)>,
+    mut cameras: Query<&mut RtsCamera>,
+) {
+    if !controls.just_pressed(GameAction::FocusSelected) {
+        return;
+    }
+
+    let Some((_, transform)) = selected.iter().find(|(selection, _)| selection.selected()) else {
+        return;
+    };
+
+    for mut camera in &mut cameras {
+        camera.focus = transform.translation().xz();
+    }
+}
+
+fn update_camera_transform(
+    level: Res<Level>,
+    mut cameras: Query<(&mut RtsCamera, &mut Transform), Changed<RtsCamera>>,
+) {
+    for (mut camera, mut transform) in &mut cameras {
+        camera.clamp_to(&level);
+        *transform = camera.transform();
+    }
+}
+
+/// The editor keeps the camera over the level being edited, not the one last played
+fn update_editor_camera_transform(
+    edited: Res<EditedLevel>,
+    mut cameras: Query<(&mut RtsCamera, &mut Transform), Changed<RtsCamera>>,
+) {
+    for (mut camera, mut transform) in &mut cameras {
+        camera.clamp_to(&edited.0);
+        *transform = camera.transform();
+    }
+}
@@ -1,16 +1,32 @@
+use std::str::FromStr;
+
 use bevy::prelude::*;
 use bevy_inspector_egui::Inspectable;
+use serde::{Deserialize, Serialize};
+
+use crate::spline::PathSpline;
 
 #[derive(Reflect, Component, Default)]
 #[reflect(Component)]
 pub struct Target {
     pub speed: f32,
-    pub path_index: usize,
+    /// How far along the path this target has walked, negative while lined up behind the spawn
+    pub distance: f32,
+    /// Tower whose bullet hit this target last, credited with the kill
+    pub last_hit_by: Option<Entity>,
 }
 
-#[derive(Resource)]
+impl Target {
+    /// Distance left to walk to the end of `path`
+    pub fn distance_to_exit(&self, path: &PathSpline) -> f32 {
+        (path.length() - self.distance).max(0.0)
+    }
+}
+
+/// The route targets walk, from the spawn through the level's waypoints
+#[derive(Resource, Default)]
 pub struct TargetPath {
-    pub waypoints: Vec<Vec2>,
+    pub spline: PathSpline,
 }
 
 #[derive(Reflect, Component, Default)]
@@ -24,21 +40,89 @@ pub struct Lifetime {
 pub struct Bullet {
     pub direction: Vec3,
     pub speed: f32,
+    pub damage: i32,
+    /// Tower that fired this bullet
+    pub source: Option<Entity>,
 }
 
+/// Towers can be upgraded up to this level
+pub const MAX_TOWER_LEVEL: u32 = 3;
+
 #[derive(Reflect, Component, Default)]
 #[reflect(Component)]
 pub struct Tower {
     pub shooting_timer: Timer,
     pub bullet_offset: Vec3,
+    /// Starts at 1, every level adds a point of damage and fires faster
+    pub level: u32,
+    /// Everything paid for this tower so far, part of it is refunded when selling
+    pub invested: u32,
 }
+
+impl Tower {
+    /// Damage done by each bullet
+    pub fn damage(&self) -> i32 {
+        self.level as i32
+    }
+
+    /// Money given back when the tower is sold
+    pub fn sell_value(&self) -> u32 {
+        self.invested / 2
+    }
+}
+
+/// Which target a tower shoots at
+#[derive(Reflect, Component, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum TargetingMode {
+    /// Furthest along the path
+    First,
+    /// Least far along the path
+    Last,
+    /// What towers always shot at before they had a choice
+    #[default]
+    Closest,
+    /// Most health left
+    Strongest,
+}
+
+impl TargetingMode {
+    pub const ALL: [TargetingMode; 4] = [
+        TargetingMode::First,
+        TargetingMode::Last,
+        TargetingMode::Closest,
+        TargetingMode::Strongest,
+    ];
+
+    pub fn next(&self) -> TargetingMode {
+        let index = TargetingMode::ALL
+            .iter()
+            .position(|mode| mode == self)
+            .unwrap_or(0);
+        TargetingMode::ALL[(index + 1) % TargetingMode::ALL.len()]
+    }
+
+    /// Text shown for the mode in the tower panel
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            TargetingMode::First => "tower-targeting-first",
+            TargetingMode::Last => "tower-targeting-last",
+            TargetingMode::Closest => "tower-targeting-closest",
+            TargetingMode::Strongest => "tower-targeting-strongest",
+        }
+    }
+}
+
+/// An empty slot a tower can be built on
+#[derive(Component)]
+pub struct TowerBase;
 #[derive(Reflect, Component, Default)]
 #[reflect(Component)]
 pub struct Health {
     pub value: i32,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct GameAssets {
     pub tower_base_scene: Handle<Scene>,
     pub tomato_tower_scene: Handle<Scene>,
@@ -53,8 +137,9 @@ pub struct GameAssets {
 #[derive(Component)]
 pub struct TowerUIRoot;
 
-#[derive(Inspectable, Component, Clone, Copy, Debug)]
-
+#[derive(
+    Inspectable, Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
 pub enum TowerType {
     Tomato,
     Potato,
@@ -62,6 +147,30 @@ pub enum TowerType {
 }
 
 impl TowerType {
+    pub const ALL: [TowerType; 3] = [TowerType::Tomato, TowerType::Potato, TowerType::Cabbage];
+
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            TowerType::Tomato => "tower-tomato",
+            TowerType::Potato => "tower-potato",
+            TowerType::Cabbage => "tower-cabbage",
+        }
+    }
+
+    /// Price of building the tower
+    pub fn cost(&self) -> u32 {
+        match self {
+            TowerType::Tomato => 50,
+            TowerType::Potato => 80,
+            TowerType::Cabbage => 110,
+        }
+    }
+
+    /// Price of upgrading a tower that is at `level`
+    pub fn upgrade_cost(&self, level: u32) -> u32 {
+        self.cost() * level
+    }
+
     pub fn get_tower(&self, assets: &GameAssets) -> (Handle<Scene>, Tower) {
         match self {
             TowerType::Tomato => (
@@ -69,6 +178,8 @@ impl TowerType {
                 Tower {
                     shooting_timer: Timer::from_seconds(0.5, TimerMode::Repeating),
                     bullet_offset: Vec3::new(0.0, 0.2, 0.0),
+                    level: 1,
+                    invested: self.cost(),
                 },
             ),
             TowerType::Potato => (
@@ -76,6 +187,8 @@ impl TowerType {
                 Tower {
                     shooting_timer: Timer::from_seconds(0.7, TimerMode::Repeating),
                     bullet_offset: Vec3::new(0.0, 0.2, 0.0),
+                    level: 1,
+                    invested: self.cost(),
                 },
             ),
             TowerType::Cabbage => (
@@ -83,6 +196,8 @@ impl TowerType {
                 Tower {
                     shooting_timer: Timer::from_seconds(0.8, TimerMode::Repeating),
                     bullet_offset: Vec3::new(0.0, 0.2, 0.0),
+                    level: 1,
+                    invested: self.cost(),
                 },
             ),
         }
@@ -95,6 +210,8 @@ impl TowerType {
                 Bullet {
                     direction,
                     speed: 3.5,
+                    damage: 1,
+                    source: None,
                 },
             ),
             TowerType::Potato => (
@@ -102,6 +219,8 @@ impl TowerType {
                 Bullet {
                     direction,
                     speed: 6.5,
+                    damage: 1,
+                    source: None,
                 },
             ),
             TowerType::Cabbage => (
@@ -109,20 +228,69 @@ impl TowerType {
                 Bullet {
                     direction,
                     speed: 8.5,
+                    damage: 1,
+                    source: None,
                 },
             ),
         }
     }
 }
 
+impl FromStr for TowerType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tomato" => Ok(TowerType::Tomato),
+            "potato" => Ok(TowerType::Potato),
+            "cabbage" => Ok(TowerType::Cabbage),
+            _ => Err(format!("unknown tower type '{}'", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum GameState {
     MainMenu,
     InGame,
+    /// Pushed on top of `InGame`, which stays loaded but stops updating
+    Paused,
     GameOver,
+    /// Every wave was sent and cleared
+    Victory,
+    /// Pushed on top of `MainMenu` or `Paused`
+    Settings,
+    /// Pushed on top of `Settings`
+    Controls,
+    /// Building a level, with a button to play it straight away
+    Editor,
+}
+
+pub struct TargetDeathEvent {
+    pub killed_by: Option<Entity>,
+}
+
+/// A target reached the end of the path
+pub struct TargetLeakedEvent;
+
+pub struct TargetHitEvent {
+    pub target: Entity,
+    pub tower: Option<Entity>,
+    pub damage: i32,
+}
+
+pub struct TowerFiredEvent {
+    pub tower: Entity,
+    pub tower_type: TowerType,
+}
+
+pub struct WaveStartedEvent {
+    /// Index of the wave in the level
+    pub wave: usize,
 }
 
-pub struct TargetDeathEvent;
+/// A tower was asked for that the player couldn't pay for
+pub struct NotEnoughMoneyEvent;
 
 #[derive(Reflect, Component, Default)]
 #[reflect(Component)]
@@ -0,0 +1,600 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use simula_action::Action;
+
+use crate::components::GameState;
+use crate::localization::{Localization, LocalizedText};
+use crate::main_menu::spawn_button;
+use crate::state_scoped::StateScoped;
+
+/// Where the game binary keeps the key bindings, relative to the working directory
+pub const KEY_BINDINGS_FILE: &str = "keybindings.ron";
+
+/// Everything the player can do from the keyboard during a match
+#[derive(Reflect, FromReflect, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameAction {
+    BuildTomato,
+    BuildPotato,
+    BuildCabbage,
+    Sell,
+    Upgrade,
+    CycleTargeting,
+    NextWave,
+    Pause,
+    SpeedNormal,
+    SpeedDouble,
+    SpeedQuadruple,
+    ToggleFlowField,
+    Playtest,
+    PanForward,
+    PanBack,
+    PanLeft,
+    PanRight,
+    RotateLeft,
+    RotateRight,
+    FocusSelected,
+}
+
+impl GameAction {
+    pub const ALL: [GameAction; 20] = [
+        GameAction::BuildTomato,
+        GameAction::BuildPotato,
+        GameAction::BuildCabbage,
+        GameAction::Sell,
+        GameAction::Upgrade,
+        GameAction::CycleTargeting,
+        GameAction::NextWave,
+        GameAction::Pause,
+        GameAction::SpeedNormal,
+        GameAction::SpeedDouble,
+        GameAction::SpeedQuadruple,
+        GameAction::ToggleFlowField,
+        GameAction::Playtest,
+        GameAction::PanForward,
+        GameAction::PanBack,
+        GameAction::PanLeft,
+        GameAction::PanRight,
+        GameAction::RotateLeft,
+        GameAction::RotateRight,
+        GameAction::FocusSelected,
+    ];
+
+    /// Message key of the name shown on the controls screen
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            GameAction::BuildTomato => "action-build-tomato",
+            GameAction::BuildPotato => "action-build-potato",
+            GameAction::BuildCabbage => "action-build-cabbage",
+            GameAction::Sell => "action-sell",
+            GameAction::Upgrade => "action-upgrade",
+            GameAction::CycleTargeting => "action-cycle-targeting",
+            GameAction::NextWave => "action-next-wave",
+            GameAction::Pause => "action-pause",
+            GameAction::SpeedNormal => "action-speed-normal",
+            GameAction::SpeedDouble => "action-speed-double",
+            GameAction::SpeedQuadruple => "action-speed-quadruple",
+            GameAction::ToggleFlowField => "action-toggle-flow-field",
+            GameAction::Playtest => "action-playtest",
+            GameAction::PanForward => "action-pan-forward",
+            GameAction::PanBack => "action-pan-back",
+            GameAction::PanLeft => "action-pan-left",
+            GameAction::PanRight => "action-pan-right",
+            GameAction::RotateLeft => "action-rotate-left",
+            GameAction::RotateRight => "action-rotate-right",
+            GameAction::FocusSelected => "action-focus-selected",
+        }
+    }
+}
+
+/// The key that triggers each action
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub keys: HashMap<GameAction, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            keys: [
+                (GameAction::BuildTomato, KeyCode::Key1),
+                (GameAction::BuildPotato, KeyCode::Key2),
+                (GameAction::BuildCabbage, KeyCode::Key3),
+                (GameAction::Sell, KeyCode::X),
+                (GameAction::Upgrade, KeyCode::U),
+                (GameAction::CycleTargeting, KeyCode::T),
+                (GameAction::NextWave, KeyCode::N),
+                (GameAction::Pause, KeyCode::Escape),
+                (GameAction::SpeedNormal, KeyCode::F1),
+                (GameAction::SpeedDouble, KeyCode::F2),
+                (GameAction::SpeedQuadruple, KeyCode::F3),
+                (GameAction::ToggleFlowField, KeyCode::F4),
+                (GameAction::Playtest, KeyCode::F5),
+                (GameAction::PanForward, KeyCode::W),
+                (GameAction::PanBack, KeyCode::S),
+                (GameAction::PanLeft, KeyCode::A),
+                (GameAction::PanRight, KeyCode::D),
+                (GameAction::RotateLeft, KeyCode::Q),
+                (GameAction::RotateRight, KeyCode::E),
+                (GameAction::FocusSelected, KeyCode::F),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads the bindings file, falling back to the defaults when it is missing or broken.
+    /// Actions missing from the file keep their default key unless another action took it.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+
+        let mut bindings: Self = ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!(
+                "ignoring invalid key bindings in {}: {}",
+                path.display(),
+                err
+            );
+            Self::default()
+        });
+        for (action, key) in Self::default().keys {
+            if !bindings.keys.contains_key(&action) && bindings.action_for(key).is_none() {
+                bindings.keys.insert(action, key);
+            }
+        }
+        bindings
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        fs::write(path, contents)
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))
+    }
+
+    pub fn key(&self, action: GameAction) -> Option<KeyCode> {
+        self.keys.get(&action).copied()
+    }
+
+    /// The key bound to `action` went down this frame.
+    ///
+    /// Menus stacked on each other read the keyboard through this, so the screen that handles
+    /// a press can clear it before the one underneath sees it.
+    pub fn just_pressed(&self, action: GameAction, keyboard: &Input<KeyCode>) -> bool {
+        self.key(action)
+            .map_or(false, |key| keyboard.just_pressed(key))
+    }
+
+    /// The action `key` triggers, if any
+    pub fn action_for(&self, key: KeyCode) -> Option<GameAction> {
+        GameAction::ALL
+            .into_iter()
+            .find(|action| self.key(*action) == Some(key))
+    }
+
+    /// Moves `action` to `key`, unless another action already uses it.
+    /// The conflicting action is returned and nothing changes.
+    pub fn bind(&mut self, action: GameAction, key: KeyCode) -> Result<(), GameAction> {
+        match self.action_for(key) {
+            Some(other) if other != action => Err(other),
+            _ => {
+                self.keys.insert(action, key);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Set by the game binary, key bindings aren't saved without it
+#[derive(Resource)]
+pub struct KeyBindingsPath(pub PathBuf);
+
+/// The action waiting for a key on the controls screen
+#[derive(Resource, Default)]
+pub struct Rebinding(pub Option<GameAction>);
+
+/// Starts rebinding its action when clicked
+#[derive(Component)]
+pub struct KeyBindingButton(pub GameAction);
+
+/// Shows the key bound to an action
+#[derive(Component)]
+pub struct KeyBindingText(pub GameAction);
+
+/// Explains why the last key was refused
+#[derive(Component)]
+pub struct ControlsStatusText;
+
+#[derive(Component)]
+pub struct ControlsUIRoot;
+
+#[derive(Component)]
+pub struct ControlsBackButton;
+
+#[derive(Component)]
+pub struct ResetBindingsButton;
+
+/// Opens the controls screen from the settings screen
+#[derive(Component)]
+pub struct ControlsButton;
+
+/// Holds the `Action<GameAction>` state fed from the key bindings
+#[derive(Component)]
+pub struct PlayerControls;
+
+/// Read access to the player's actions for gameplay systems
+#[derive(SystemParam)]
+pub struct Controls<'w, 's> {
+    actions: Query<'w, 's, &'static Action<GameAction>, With<PlayerControls>>,
+}
+
+impl Controls<'_, '_> {
+    /// The action's key is held down
+    pub fn pressed(&self, action: GameAction) -> bool {
+        self.actions
+            .get_single()
+            .map_or(false, |actions| actions.is_on(action))
+    }
+
+    /// The action's key went down this frame
+    pub fn just_pressed(&self, action: GameAction) -> bool {
+        self.actions
+            .get_single()
+            .map_or(false, |actions| actions.on_enter(action))
+    }
+}
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GameAction>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<Rebinding>()
+            .add_startup_system(spawn_player_controls)
+            // After the input is read, so every Update system sees this frame's actions
+            .add_system_to_stage(CoreStage::PreUpdate, map_actions.after(InputSystem))
+            .add_system(controls_button_clicked)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Controls).with_system(spawn_controls_menu),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Controls).with_system(save_key_bindings))
+            .add_system_set(
+                SystemSet::on_update(GameState::Controls)
+                    .with_system(key_binding_button_clicked)
+                    .with_system(reset_bindings_button_clicked)
+                    .with_system(capture_key.after(key_binding_button_clicked))
+                    .with_system(
+                        update_key_binding_texts
+                            .after(capture_key)
+                            .after(reset_bindings_button_clicked),
+                    )
+                    .with_system(controls_back.after(capture_key)),
+            );
+    }
+}
+
+fn spawn_player_controls(mut commands: Commands) {
+    commands.spawn((
+        PlayerControls,
+        Action::<GameAction>::default(),
+        Name::new("Player_Controls"),
+    ));
+}
+
+fn map_actions(
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut actions: Query<&mut Action<GameAction>, With<PlayerControls>>,
+) {
+    for mut action_state in &mut actions {
+        for action in GameAction::ALL {
+            let pressed = bindings
+                .keys
+                .get(&action)
+                .map_or(false, |key| keyboard.pressed(*key));
+
+            if pressed {
+                action_state.on(action);
+            } else {
+                action_state.off(action);
+            }
+        }
+    }
+}
+
+fn save_key_bindings(
+    bindings: Res<KeyBindings>,
+    path: Option<Res<KeyBindingsPath>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    rebinding.0 = None;
+
+    let Some(path) = path else {
+        return;
+    };
+
+    if let Err(err) = bindings.save(&path.0) {
+        warn!("{}", err);
+    }
+}
+
+// Only exists while the settings screen is the current state
+fn controls_button_clicked(
+    interactions: Query<&Interaction, (With<ControlsButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            let _ = game_state.push(GameState::Controls);
+        }
+    }
+}
+
+fn key_binding_button_clicked(
+    interactions: Query<(&Interaction, &KeyBindingButton), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut status: Query<&mut LocalizedText, With<ControlsStatusText>>,
+) {
+    for (interaction, button) in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            rebinding.0 = Some(button.0);
+            for mut text in &mut status {
+                *text = LocalizedText::new("controls-press-key");
+            }
+        }
+    }
+}
+
+fn reset_bindings_button_clicked(
+    interactions: Query<&Interaction, (With<ResetBindingsButton>, Changed<Interaction>)>,
+    mut bindings: ResMut<KeyBindings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            *bindings = KeyBindings::default();
+            rebinding.0 = None;
+        }
+    }
+}
+
+/// Binds the first key pressed while an action is waiting, Escape cancels instead
+fn capture_key(
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<KeyBindings>,
+    localization: Res<Localization>,
+    mut status: Query<&mut LocalizedText, With<ControlsStatusText>>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let Some(key) = keyboard.get_just_pressed().next().copied() else {
+        return;
+    };
+    // Handled here, so it doesn't also close the screen
+    keyboard.clear_just_pressed(key);
+    rebinding.0 = None;
+
+    let message = if key == KeyCode::Escape {
+        LocalizedText::default()
+    } else {
+        match bindings.bind(action, key) {
+            Ok(()) => LocalizedText::default(),
+            Err(other) => LocalizedText::new("controls-conflict")
+                .with_arg("key", format!("{:?}", key))
+                .with_arg("action", localization.get(other.text_key())),
+        }
+    };
+
+    for mut text in &mut status {
+        *text = message.clone();
+    }
+}
+
+fn update_key_binding_texts(
+    bindings: Res<KeyBindings>,
+    rebinding: Res<Rebinding>,
+    localization: Res<Localization>,
+    mut texts: Query<(&mut Text, &KeyBindingText)>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    for (mut text, binding) in &mut texts {
+        text.sections[0].value = key_binding_text(binding.0, &bindings, &rebinding, &localization);
+    }
+}
+
+fn key_binding_text(
+    action: GameAction,
+    bindings: &KeyBindings,
+    rebinding: &Rebinding,
+    localization: &Localization,
+) -> String {
+    if rebinding.0 == Some(action) {
+        return "...".to_string();
+    }
+
+    match bindings.key(action) {
+        Some(key) => format!("{:?}", key),
+        None => localization.get("controls-unbound"),
+    }
+}
+
+fn controls_back(
+    interactions: Query<&Interaction, (With<ControlsBackButton>, Changed<Interaction>)>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    let clicked = interactions
+        .iter()
+        .any(|interaction| matches!(interaction, Interaction::Clicked));
+
+    if clicked || bindings.just_pressed(GameAction::Pause, &keyboard) {
+        // The settings screen underneath would close on the same press
+        if let Some(key) = bindings.key(GameAction::Pause) {
+            keyboard.clear_just_pressed(key);
+        }
+        let _ = game_state.pop();
+    }
+}
+
+fn spawn_controls_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bindings: Res<KeyBindings>,
+    rebinding: Res<Rebinding>,
+    localization: Res<Localization>,
+) {
+    let reset_button = spawn_button(&mut commands, &asset_server, "controls-reset", Color::GRAY);
+    commands.entity(reset_button).insert(ResetBindingsButton);
+
+    let back_button = spawn_button(&mut commands, &asset_server, "button-back", Color::BLUE);
+    commands.entity(back_button).insert(ControlsBackButton);
+
+    let text_style = |font_size: f32| TextStyle {
+        font: asset_server.load("FiraSans-Bold.ttf"),
+        font_size,
+        color: Color::BLACK,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            // Drawn over the settings screen
+            background_color: Color::rgb(0.8, 0.8, 0.8).into(),
+            z_index: ZIndex::Global(30),
+            ..default()
+        })
+        .insert(ControlsUIRoot)
+        .insert(StateScoped(GameState::Controls))
+        .with_children(|commands| {
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Percent(2.0)),
+                        ..default()
+                    },
+                    text: Text::from_section("", text_style(64.0)),
+                    ..default()
+                })
+                .insert(LocalizedText::new("controls-title"));
+
+            // Two columns, so every action fits on screen
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|commands| {
+                    for column in GameAction::ALL.chunks((GameAction::ALL.len() + 1) / 2) {
+                        commands
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    margin: UiRect::horizontal(Val::Px(16.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|commands| {
+                                for &action in column {
+                                    let key = key_binding_text(
+                                        action,
+                                        &bindings,
+                                        &rebinding,
+                                        &localization,
+                                    );
+                                    spawn_key_binding_row(commands, action, key, &text_style(28.0));
+                                }
+                            });
+                    }
+                });
+
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    text: Text::from_section("", text_style(32.0)),
+                    ..default()
+                })
+                .insert(LocalizedText::default())
+                .insert(ControlsStatusText);
+        })
+        .push_children(&[reset_button, back_button]);
+}
+
+fn spawn_key_binding_row(
+    commands: &mut ChildBuilder,
+    action: GameAction,
+    key: String,
+    text_style: &TextStyle,
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|commands| {
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(280.0), Val::Auto),
+                        ..default()
+                    },
+                    text: Text::from_section("", text_style.clone()),
+                    ..default()
+                })
+                .insert(LocalizedText::new(action.text_key()));
+
+            commands
+                .spawn(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(160.0), Val::Px(40.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(4.0)),
+                        ..default()
+                    },
+                    background_color: Color::GRAY.into(),
+                    ..default()
+                })
+                .insert(KeyBindingButton(action))
+                .with_children(|commands| {
+                    commands
+                        .spawn(TextBundle::from_section(key, text_style.clone()))
+                        .insert(KeyBindingText(action));
+                });
+        });
+}
@@ -0,0 +1,196 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::GameState;
+use crate::localization::LocalizedText;
+
+/// Chosen in the main menu, scales the targets and the player's starting resources
+#[derive(
+    Resource, Component, Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Nightmare,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    /// Message key of the name shown to the player
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty-easy",
+            Difficulty::Normal => "difficulty-normal",
+            Difficulty::Hard => "difficulty-hard",
+            Difficulty::Nightmare => "difficulty-nightmare",
+        }
+    }
+
+    /// Multiplies the health of every target in the level's waves
+    pub fn health_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+            Difficulty::Nightmare => 2.0,
+        }
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.85,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.15,
+            Difficulty::Nightmare => 1.3,
+        }
+    }
+
+    /// Money given for every target killed
+    pub fn bounty(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 15,
+            Difficulty::Normal => 10,
+            Difficulty::Hard => 8,
+            Difficulty::Nightmare => 5,
+        }
+    }
+
+    pub fn starting_money(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 150,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 80,
+            Difficulty::Nightmare => 60,
+        }
+    }
+
+    pub fn starting_lives(&self) -> u32 {
+        match self {
+            Difficulty::Easy => 20,
+            Difficulty::Normal => 10,
+            Difficulty::Hard => 5,
+            Difficulty::Nightmare => 1,
+        }
+    }
+
+    /// Health of a target from a wave with `health`, never below one
+    pub fn target_health(&self, health: i32) -> i32 {
+        ((health as f32 * self.health_multiplier()).round() as i32).max(1)
+    }
+
+    pub fn target_speed(&self, speed: f32) -> f32 {
+        speed * self.speed_multiplier()
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown difficulty '{}'", s))
+    }
+}
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>().add_system_set(
+            SystemSet::on_update(GameState::MainMenu)
+                .with_system(difficulty_button_clicked)
+                .with_system(highlight_difficulty_buttons.after(difficulty_button_clicked)),
+        );
+    }
+}
+
+fn difficulty_button_clicked(
+    interactions: Query<(&Interaction, &Difficulty), Changed<Interaction>>,
+    mut selected: ResMut<Difficulty>,
+) {
+    for (interaction, difficulty) in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            *selected = *difficulty;
+        }
+    }
+}
+
+fn highlight_difficulty_buttons(
+    mut buttons: Query<(&mut BackgroundColor, &Difficulty)>,
+    selected: Res<Difficulty>,
+) {
+    for (mut color, difficulty) in &mut buttons {
+        *color = if *difficulty == *selected {
+            Color::WHITE.into()
+        } else {
+            Color::GRAY.into()
+        };
+    }
+}
+
+/// A row with one button per difficulty, for the main menu
+pub(crate) fn spawn_difficulty_buttons(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+) -> Entity {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|commands| {
+            for difficulty in Difficulty::ALL {
+                commands
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(180.0), Val::Px(56.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(difficulty)
+                    .with_children(|commands| {
+                        commands
+                            .spawn(TextBundle::from_section(
+                                "",
+                                TextStyle {
+                                    font: asset_server.load("FiraSans-Bold.ttf"),
+                                    font_size: 36.0,
+                                    color: Color::BLACK,
+                                },
+                            ))
+                            .insert(LocalizedText::new(difficulty.text_key()));
+                    });
+            }
+        })
+        .id()
+}
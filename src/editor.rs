@@ -0,0 +1,584 @@
+use bevy::{math::Vec3Swizzles, pbr::NotShadowCaster, prelude::*};
+use bevy_egui::{egui, EguiContext};
+
+use crate::build_grid::CursorGroundPosition;
+use crate::components::GameState;
+use crate::controls::{GameAction, KeyBindings};
+use crate::level::{Level, Wave};
+use crate::localization::Localization;
+use crate::path_view::{road_mesh, ROAD_WIDTH};
+use crate::spline::PathSpline;
+use crate::state_scoped::StateScoped;
+
+/// Where levels are exported to unless another file is chosen
+pub const DEFAULT_EXPORT_PATH: &str = "assets/levels/custom.ron";
+/// Clicks this close to a point grab it instead of adding a new one
+pub const PICK_RADIUS: f32 = 0.6;
+pub const MIN_FLOOR_SIZE: f32 = 10.0;
+pub const MAX_FLOOR_SIZE: f32 = 100.0;
+
+/// What clicking on the floor edits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EditorTool {
+    #[default]
+    Waypoints,
+    TowerSlots,
+    Spawn,
+}
+
+impl EditorTool {
+    pub const ALL: [EditorTool; 3] = [
+        EditorTool::Waypoints,
+        EditorTool::TowerSlots,
+        EditorTool::Spawn,
+    ];
+
+    fn text_key(&self) -> &'static str {
+        match self {
+            EditorTool::Waypoints => "editor-tool-waypoints",
+            EditorTool::TowerSlots => "editor-tool-slots",
+            EditorTool::Spawn => "editor-tool-spawn",
+        }
+    }
+}
+
+/// A point of the level being edited, `index` is unused for the spawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EditorHandle {
+    pub tool: EditorTool,
+    pub index: usize,
+}
+
+impl EditorHandle {
+    /// The point of the kind `tool` edits nearest to `position`, if any is within `PICK_RADIUS`
+    pub fn at(level: &Level, tool: EditorTool, position: Vec2) -> Option<Self> {
+        let points = match tool {
+            EditorTool::Waypoints => &level.waypoints,
+            EditorTool::TowerSlots => &level.tower_slots,
+            EditorTool::Spawn => std::slice::from_ref(&level.spawn),
+        };
+
+        points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| (index, point.distance(position)))
+            .filter(|(_, distance)| *distance <= PICK_RADIUS)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| Self { tool, index })
+    }
+
+    fn point(&self, level: &Level) -> Option<Vec2> {
+        match self.tool {
+            EditorTool::Waypoints => level.waypoints.get(self.index).copied(),
+            EditorTool::TowerSlots => level.tower_slots.get(self.index).copied(),
+            EditorTool::Spawn => Some(level.spawn),
+        }
+    }
+
+    fn point_mut<'a>(&self, level: &'a mut Level) -> Option<&'a mut Vec2> {
+        match self.tool {
+            EditorTool::Waypoints => level.waypoints.get_mut(self.index),
+            EditorTool::TowerSlots => level.tower_slots.get_mut(self.index),
+            EditorTool::Spawn => Some(&mut level.spawn),
+        }
+    }
+}
+
+/// The level being edited. It only becomes the `Level` when it is playtested, so the main
+/// menu never starts a level that hasn't been checked to be playable.
+#[derive(Resource, Default)]
+pub struct EditedLevel(pub Level);
+
+/// The level editor's own state, the level being edited is the `EditedLevel` resource
+#[derive(Resource)]
+pub struct LevelEditor {
+    pub tool: EditorTool,
+    /// The point following the cursor while the left button is held
+    pub dragging: Option<EditorHandle>,
+    pub export_path: String,
+    /// Result of the last export or playtest attempt, shown in the panel
+    pub status: Option<String>,
+}
+
+impl Default for LevelEditor {
+    fn default() -> Self {
+        Self {
+            tool: EditorTool::default(),
+            dragging: None,
+            export_path: DEFAULT_EXPORT_PATH.to_string(),
+            status: None,
+        }
+    }
+}
+
+/// Added while a level from the editor is being played, so the match can go back to it
+#[derive(Resource)]
+pub struct Playtest;
+
+#[derive(Component)]
+pub struct EditorButton;
+
+/// Everything drawn for the level being edited, rebuilt whenever it changes
+#[derive(Component)]
+pub struct EditorScene;
+
+/// A point that can be dragged around in the editor
+#[derive(Component)]
+pub struct EditorPoint(pub EditorHandle);
+
+#[derive(Resource)]
+struct EditorAssets {
+    point_mesh: Handle<Mesh>,
+    slot_mesh: Handle<Mesh>,
+    waypoint_material: Handle<StandardMaterial>,
+    slot_material: Handle<StandardMaterial>,
+    spawn_material: Handle<StandardMaterial>,
+    road_material: Handle<StandardMaterial>,
+    floor_material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for EditorAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let point_mesh = meshes.add(
+            shape::UVSphere {
+                radius: 0.3,
+                ..default()
+            }
+            .into(),
+        );
+        let slot_mesh = meshes.add(shape::Capsule::default().into());
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            point_mesh,
+            slot_mesh,
+            waypoint_material: materials.add(Color::YELLOW.into()),
+            slot_material: materials.add(Color::GRAY.into()),
+            spawn_material: materials.add(Color::rgb(0.2, 0.4, 1.0).into()),
+            road_material: materials.add(Color::rgb(0.55, 0.4, 0.25).into()),
+            floor_material: materials.add(Color::rgb(0.2, 1.0, 0.2).into()),
+        }
+    }
+}
+
+/// Locale key of the reason `level` can't be played yet, if there is one
+pub fn unplayable_reason(level: &Level) -> Option<&'static str> {
+    if level.waypoints.is_empty() {
+        Some("editor-needs-waypoint")
+    } else if level.waves.is_empty() {
+        Some("editor-needs-wave")
+    } else {
+        None
+    }
+}
+
+pub struct LevelEditorPlugin;
+
+impl Plugin for LevelEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelEditor>()
+            .init_resource::<EditedLevel>()
+            .init_resource::<EditorAssets>()
+            .add_system_set(SystemSet::on_enter(GameState::Editor).with_system(enter_editor))
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(end_playtest))
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu).with_system(editor_button_clicked),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor)
+                    .with_system(editor_panel)
+                    .with_system(edit_points.after(editor_panel))
+                    .with_system(draw_editor_level.after(edit_points)),
+            )
+            .add_system(toggle_playtest);
+    }
+}
+
+fn editor_button_clicked(
+    interactions: Query<&Interaction, (With<EditorButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+    mut mouse_input: ResMut<Input<MouseButton>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            game_state.set(GameState::Editor).unwrap();
+            // The click would otherwise add a point on the first frame of the editor
+            mouse_input.clear();
+        }
+    }
+}
+
+/// Editing starts from the level the main menu would play, and carries on from there after
+fn enter_editor(
+    mut commands: Commands,
+    mut editor: ResMut<LevelEditor>,
+    level: Res<Level>,
+    mut edited: ResMut<EditedLevel>,
+    mut started: Local<bool>,
+) {
+    commands.remove_resource::<Playtest>();
+    editor.dragging = None;
+    if !*started {
+        edited.0 = level.clone();
+        *started = true;
+    }
+
+    commands
+        .spawn(PointLightBundle {
+            point_light: PointLight {
+                intensity: 1500.0,
+                ..default()
+            },
+            transform: Transform::from_xyz(4.0, 8.0, 4.0),
+            ..default()
+        })
+        .insert(Name::new("Editor_Light"))
+        .insert(StateScoped(GameState::Editor));
+}
+
+fn end_playtest(mut commands: Commands) {
+    commands.remove_resource::<Playtest>();
+}
+
+/// The playtest key plays the level being edited, and goes back to editing it from the match
+fn toggle_playtest(
+    mut commands: Commands,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    playtest: Option<Res<Playtest>>,
+    edited: Res<EditedLevel>,
+    mut level: ResMut<Level>,
+    localization: Res<Localization>,
+    mut editor: ResMut<LevelEditor>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if !bindings.just_pressed(GameAction::Playtest, &keyboard) {
+        return;
+    }
+
+    match game_state.current() {
+        GameState::Editor => start_playtest(
+            &mut commands,
+            &edited.0,
+            &mut level,
+            &localization,
+            &mut editor,
+            &mut game_state,
+        ),
+        // Not from the pause menu, it sits on top of the match
+        GameState::InGame | GameState::GameOver | GameState::Victory if playtest.is_some() => {
+            let _ = game_state.set(GameState::Editor);
+        }
+        _ => {}
+    }
+}
+
+/// Installs the edited level as the one to play, if it can be played
+fn start_playtest(
+    commands: &mut Commands,
+    edited: &Level,
+    level: &mut Level,
+    localization: &Localization,
+    editor: &mut LevelEditor,
+    game_state: &mut State<GameState>,
+) {
+    if let Some(reason) = unplayable_reason(edited) {
+        editor.status = Some(localization.get(reason));
+        return;
+    }
+
+    editor.status = None;
+    *level = edited.clone();
+    commands.insert_resource(Playtest);
+    let _ = game_state.set(GameState::InGame);
+}
+
+/// Left click adds a point of the current tool's kind or grabs one to drag, right click removes it
+fn edit_points(
+    mouse: Res<Input<MouseButton>>,
+    cursor: Res<CursorGroundPosition>,
+    egui: Option<ResMut<EguiContext>>,
+    mut editor: ResMut<LevelEditor>,
+    mut edited: ResMut<EditedLevel>,
+) {
+    if mouse.just_released(MouseButton::Left) {
+        editor.dragging = None;
+    }
+
+    let Some(position) = cursor.0 else {
+        return;
+    };
+    let half_size = Vec2::splat(edited.0.floor_size / 2.0);
+    let position = position.clamp(-half_size, half_size);
+
+    if let Some(handle) = editor.dragging {
+        // Only touching the level when the point moves, every change redraws it
+        if mouse.pressed(MouseButton::Left) && handle.point(&edited.0) != Some(position) {
+            if let Some(point) = handle.point_mut(&mut edited.0) {
+                *point = position;
+            }
+        }
+        return;
+    }
+
+    // Clicks on the panel are for the panel
+    if egui.map_or(false, |mut egui| egui.ctx_mut().is_pointer_over_area()) {
+        return;
+    }
+
+    let tool = editor.tool;
+    let picked = EditorHandle::at(&edited.0, tool, position);
+    if mouse.just_pressed(MouseButton::Left) {
+        editor.dragging = picked.or_else(|| {
+            let index = match tool {
+                EditorTool::Waypoints => {
+                    edited.0.waypoints.push(position);
+                    edited.0.waypoints.len() - 1
+                }
+                EditorTool::TowerSlots => {
+                    edited.0.tower_slots.push(position);
+                    edited.0.tower_slots.len() - 1
+                }
+                EditorTool::Spawn => {
+                    edited.0.spawn = position;
+                    0
+                }
+            };
+            Some(EditorHandle { tool, index })
+        });
+    } else if mouse.just_pressed(MouseButton::Right) {
+        match picked {
+            Some(EditorHandle {
+                tool: EditorTool::Waypoints,
+                index,
+            }) => {
+                edited.0.waypoints.remove(index);
+            }
+            Some(EditorHandle {
+                tool: EditorTool::TowerSlots,
+                index,
+            }) => {
+                edited.0.tower_slots.remove(index);
+            }
+            // There is always a spawn, it can only be moved
+            _ => {}
+        }
+    }
+}
+
+/// Respawns the floor, path and points whenever the edited level changes
+fn draw_editor_level(
+    mut commands: Commands,
+    edited: Res<EditedLevel>,
+    assets: Res<EditorAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    scenes: Query<Entity, With<EditorScene>>,
+) {
+    if !edited.is_changed() && !scenes.is_empty() {
+        return;
+    }
+    let level = &edited.0;
+
+    for scene in &scenes {
+        commands.entity(scene).despawn_recursive();
+    }
+
+    let point = |handle: EditorHandle, position: Vec2, mesh: &Handle<Mesh>, height: f32| {
+        let material = match handle.tool {
+            EditorTool::Waypoints => &assets.waypoint_material,
+            EditorTool::TowerSlots => &assets.slot_material,
+            EditorTool::Spawn => &assets.spawn_material,
+        };
+        (
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(position.extend(height).xzy()),
+                ..default()
+            },
+            EditorPoint(handle),
+        )
+    };
+
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(
+                shape::Plane {
+                    size: level.floor_size,
+                }
+                .into(),
+            ),
+            material: assets.floor_material.clone(),
+            ..default()
+        })
+        .insert(EditorScene)
+        .insert(Name::new("Editor_Level"))
+        .insert(StateScoped(GameState::Editor))
+        .with_children(|commands| {
+            let spline = PathSpline::new(level.path());
+            if spline.length() > 0.0 {
+                commands
+                    .spawn(PbrBundle {
+                        mesh: meshes.add(road_mesh(&spline, ROAD_WIDTH)),
+                        material: assets.road_material.clone(),
+                        ..default()
+                    })
+                    .insert(NotShadowCaster);
+            }
+
+            commands.spawn(point(
+                EditorHandle {
+                    tool: EditorTool::Spawn,
+                    index: 0,
+                },
+                level.spawn,
+                &assets.point_mesh,
+                0.3,
+            ));
+            for (index, waypoint) in level.waypoints.iter().enumerate() {
+                let handle = EditorHandle {
+                    tool: EditorTool::Waypoints,
+                    index,
+                };
+                commands.spawn(point(handle, *waypoint, &assets.point_mesh, 0.3));
+            }
+            for (index, slot) in level.tower_slots.iter().enumerate() {
+                let handle = EditorHandle {
+                    tool: EditorTool::TowerSlots,
+                    index,
+                };
+                commands.spawn(point(handle, *slot, &assets.slot_mesh, 0.8));
+            }
+        });
+}
+
+fn editor_panel(
+    mut commands: Commands,
+    egui: Option<ResMut<EguiContext>>,
+    mut level: ResMut<Level>,
+    mut edited_level: ResMut<EditedLevel>,
+    mut editor: ResMut<LevelEditor>,
+    localization: Res<Localization>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    let Some(mut egui) = egui else {
+        return;
+    };
+
+    // Edits go to a copy, so the level only counts as changed when something really did
+    let mut edited = edited_level.0.clone();
+    let mut playtest = false;
+    let mut back = false;
+
+    egui::SidePanel::left("level_editor").show(egui.ctx_mut(), |ui| {
+        ui.heading(localization.get("editor-title"));
+
+        ui.horizontal(|ui| {
+            ui.label(localization.get("editor-name"));
+            ui.text_edit_singleline(&mut edited.name);
+        });
+        ui.add(
+            egui::Slider::new(&mut edited.floor_size, MIN_FLOOR_SIZE..=MAX_FLOOR_SIZE)
+                .text(localization.get("editor-floor-size")),
+        );
+        ui.add(
+            egui::Slider::new(&mut edited.wave_delay, 0.0..=30.0)
+                .text(localization.get("editor-wave-delay")),
+        );
+
+        ui.separator();
+        for tool in EditorTool::ALL {
+            ui.radio_value(&mut editor.tool, tool, localization.get(tool.text_key()));
+        }
+        ui.label(localization.get("editor-hint"));
+
+        ui.separator();
+        ui.label(localization.get("editor-waves"));
+        let mut removed = None;
+        egui::Grid::new("editor_waves").show(ui, |ui| {
+            for key in [
+                "editor-wave-count",
+                "editor-wave-health",
+                "editor-wave-speed",
+                "editor-wave-spacing",
+            ] {
+                ui.label(localization.get(key));
+            }
+            ui.end_row();
+
+            for (index, wave) in edited.waves.iter_mut().enumerate() {
+                ui.add(egui::DragValue::new(&mut wave.count).clamp_range(1..=500));
+                ui.add(egui::DragValue::new(&mut wave.health).clamp_range(1..=1000));
+                ui.add(
+                    egui::DragValue::new(&mut wave.speed)
+                        .clamp_range(0.05..=10.0)
+                        .speed(0.05),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut wave.spacing)
+                        .clamp_range(0.1..=10.0)
+                        .speed(0.1),
+                );
+                if ui.small_button("✖").clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = removed {
+            edited.waves.remove(index);
+        }
+        if ui.button(localization.get("editor-add-wave")).clicked() {
+            // The last wave is a good start for the next one
+            let wave = edited
+                .waves
+                .last()
+                .cloned()
+                .unwrap_or_else(|| Level::default().waves[0].clone());
+            edited.waves.push(Wave {
+                count: wave.count + 4,
+                ..wave
+            });
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(localization.get("editor-file"));
+            ui.text_edit_singleline(&mut editor.export_path);
+        });
+        ui.horizontal(|ui| {
+            if ui.button(localization.get("editor-export")).clicked() {
+                editor.status = Some(match edited.save(&editor.export_path) {
+                    Ok(()) => localization.format(
+                        "editor-exported",
+                        &[("path".to_string(), editor.export_path.clone())],
+                    ),
+                    Err(err) => err,
+                });
+            }
+            playtest = ui.button(localization.get("editor-playtest")).clicked();
+            back = ui.button(localization.get("button-main-menu")).clicked();
+        });
+        if let Some(status) = &editor.status {
+            ui.label(status);
+        }
+    });
+
+    if edited != edited_level.0 {
+        edited_level.0 = edited;
+    }
+
+    if playtest {
+        start_playtest(
+            &mut commands,
+            &edited_level.0,
+            &mut level,
+            &localization,
+            &mut editor,
+            &mut game_state,
+        );
+    } else if back {
+        let _ = game_state.set(GameState::MainMenu);
+    }
+}
@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+
+use crate::components::TowerType;
+use crate::difficulty::Difficulty;
+use crate::high_scores::HighScoreTable;
+use crate::level::Level;
+use crate::localization::{Localization, LocalizedText};
+use crate::main_menu::spawn_button;
+use crate::state_scoped::StateScoped;
+use crate::stats::MatchStats;
+use crate::wave::WaveProgress;
+use crate::GameState;
+
+/// Root of the results shown after a match, either `GameState::GameOver` or `GameState::Victory`
+#[derive(Component)]
+pub struct EndScreenUIRoot;
+
+#[derive(Component)]
+pub struct PlayAgainButton;
+
+#[derive(Component)]
+pub struct MainMenuButton;
+
+pub struct EndScreenPlugin;
+
+impl Plugin for EndScreenPlugin {
+    fn build(&self, app: &mut App) {
+        for state in [GameState::GameOver, GameState::Victory] {
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(spawn_end_screen))
+                .add_system_set(
+                    SystemSet::on_update(state)
+                        .with_system(play_again_button_clicked)
+                        .with_system(main_menu_button_clicked),
+                );
+        }
+    }
+}
+
+fn play_again_button_clicked(
+    interactions: Query<&Interaction, (With<PlayAgainButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+    mut mouse_input: ResMut<Input<MouseButton>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            game_state.set(GameState::InGame).unwrap();
+            mouse_input.clear();
+        }
+    }
+}
+
+fn main_menu_button_clicked(
+    interactions: Query<&Interaction, (With<MainMenuButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            game_state.set(GameState::MainMenu).unwrap();
+        }
+    }
+}
+
+fn spawn_end_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_state: Res<State<GameState>>,
+    stats: Res<MatchStats>,
+    progress: Res<WaveProgress>,
+    level: Res<Level>,
+    difficulty: Res<Difficulty>,
+    localization: Res<Localization>,
+) {
+    let victory = *game_state.current() == GameState::Victory;
+
+    let play_again_button =
+        spawn_button(&mut commands, &asset_server, "button-restart", Color::RED);
+    commands.entity(play_again_button).insert(PlayAgainButton);
+
+    let main_menu_button = spawn_button(
+        &mut commands,
+        &asset_server,
+        "button-main-menu",
+        Color::BLUE,
+    );
+    commands.entity(main_menu_button).insert(MainMenuButton);
+
+    let seconds = format!("{:.0}", stats.duration.as_secs_f32());
+    let (title, summary) = if victory {
+        (
+            LocalizedText::new("end-victory-title").with_arg("stars", stars_text(stats.stars())),
+            vec![
+                LocalizedText::new("end-score").with_arg("score", stats.score()),
+                LocalizedText::new("end-lives-left").with_arg("lives", stats.lives_left),
+                LocalizedText::new("end-cleared-in").with_arg("seconds", seconds),
+            ],
+        )
+    } else {
+        (
+            LocalizedText::new("end-game-over-title"),
+            vec![
+                LocalizedText::new("end-reached-wave")
+                    .with_arg("wave", progress.next)
+                    .with_arg("waves", level.waves.len()),
+                LocalizedText::new("end-survived").with_arg("seconds", seconds),
+                LocalizedText::new("end-score").with_arg("score", stats.score()),
+            ],
+        )
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(EndScreenUIRoot)
+        .insert(StateScoped(game_state.current().clone()))
+        .with_children(|commands| {
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Percent(2.0)),
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 96.0,
+                            color: Color::BLACK,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(title);
+
+            // The end screen can't open the settings, so the difficulty name won't need to change
+            let common = [
+                LocalizedText::new("end-difficulty")
+                    .with_arg("difficulty", localization.get(difficulty.text_key())),
+                LocalizedText::new("end-kills").with_arg("kills", stats.kills),
+                LocalizedText::new("end-money-earned").with_arg("money", stats.money_earned),
+            ];
+            // How each tower type did, for the ones that were built
+            let towers = TowerType::ALL.into_iter().filter_map(|tower_type| {
+                let tower_stats = stats.tower_type(tower_type);
+                (tower_stats.shots > 0).then(|| {
+                    LocalizedText::new("end-tower-summary")
+                        .with_arg("tower", localization.get(tower_type.text_key()))
+                        .with_arg("kills", tower_stats.kills)
+                        .with_arg("damage", tower_stats.damage)
+                        .with_arg("hits", tower_stats.hits)
+                        .with_arg("shots", tower_stats.shots)
+                })
+            });
+            for line in summary.into_iter().chain(common).chain(towers) {
+                commands
+                    .spawn(TextBundle {
+                        style: Style {
+                            align_self: AlignSelf::Center,
+                            ..default()
+                        },
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font: asset_server.load("FiraSans-Bold.ttf"),
+                                font_size: 36.0,
+                                color: Color::BLACK,
+                            },
+                        ),
+                        ..default()
+                    })
+                    .insert(line);
+            }
+        })
+        .with_children(|commands| {
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Percent(1.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(HighScoreTable);
+        })
+        .add_child(play_again_button)
+        .add_child(main_menu_button);
+}
+
+fn stars_text(stars: u32) -> String {
+    (0..3)
+        .map(|star| if star < stars { '*' } else { '-' })
+        .collect()
+}
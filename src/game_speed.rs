@@ -0,0 +1,164 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::components::GameState;
+use crate::controls::{Controls, GameAction};
+use crate::state_scoped::StateScoped;
+
+/// How fast the match plays, switched from the HUD or with the speed actions, F1/F2/F3 by default
+#[derive(Resource, Component, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameSpeed {
+    #[default]
+    Normal,
+    Double,
+    Quadruple,
+}
+
+impl GameSpeed {
+    pub const ALL: [GameSpeed; 3] = [GameSpeed::Normal, GameSpeed::Double, GameSpeed::Quadruple];
+
+    pub fn multiplier(&self) -> f32 {
+        match self {
+            GameSpeed::Normal => 1.0,
+            GameSpeed::Double => 2.0,
+            GameSpeed::Quadruple => 4.0,
+        }
+    }
+
+    fn action(&self) -> GameAction {
+        match self {
+            GameSpeed::Normal => GameAction::SpeedNormal,
+            GameSpeed::Double => GameAction::SpeedDouble,
+            GameSpeed::Quadruple => GameAction::SpeedQuadruple,
+        }
+    }
+}
+
+/// `Time` as seen by gameplay systems, scaled by the current `GameSpeed`.
+///
+/// The camera and UI keep using `Time` directly so they don't speed up with the match.
+#[derive(SystemParam)]
+pub struct GameTime<'w, 's> {
+    time: Res<'w, Time>,
+    speed: Res<'w, GameSpeed>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl GameTime<'_, '_> {
+    pub fn delta(&self) -> Duration {
+        self.time.delta().mul_f32(self.speed.multiplier())
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.time.delta_seconds() * self.speed.multiplier()
+    }
+}
+
+#[derive(Component)]
+pub struct GameSpeedUIRoot;
+
+pub struct GameSpeedPlugin;
+
+impl Plugin for GameSpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameSpeed>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .with_system(reset_game_speed)
+                    .with_system(spawn_game_speed_ui),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(game_speed_hotkeys)
+                    .with_system(game_speed_button_clicked)
+                    .with_system(
+                        highlight_game_speed_buttons
+                            .after(game_speed_hotkeys)
+                            .after(game_speed_button_clicked),
+                    ),
+            );
+    }
+}
+
+fn reset_game_speed(mut speed: ResMut<GameSpeed>) {
+    *speed = GameSpeed::Normal;
+}
+
+fn game_speed_hotkeys(controls: Controls, mut speed: ResMut<GameSpeed>) {
+    for game_speed in GameSpeed::ALL {
+        if controls.just_pressed(game_speed.action()) {
+            *speed = game_speed;
+        }
+    }
+}
+
+fn game_speed_button_clicked(
+    interactions: Query<(&Interaction, &GameSpeed), Changed<Interaction>>,
+    mut speed: ResMut<GameSpeed>,
+) {
+    for (interaction, game_speed) in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            *speed = *game_speed;
+        }
+    }
+}
+
+fn highlight_game_speed_buttons(
+    mut buttons: Query<(&mut BackgroundColor, &GameSpeed)>,
+    speed: Res<GameSpeed>,
+) {
+    for (mut color, game_speed) in &mut buttons {
+        *color = if *game_speed == *speed {
+            Color::WHITE.into()
+        } else {
+            Color::GRAY.into()
+        };
+    }
+}
+
+fn spawn_game_speed_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Percent(1.2),
+                    bottom: Val::Percent(1.2),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(GameSpeedUIRoot)
+        .insert(StateScoped(GameState::InGame))
+        .with_children(|commands| {
+            for game_speed in GameSpeed::ALL {
+                commands
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(64.0), Val::Px(40.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            margin: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(game_speed)
+                    .with_children(|commands| {
+                        commands.spawn(TextBundle::from_section(
+                            format!("{}x", game_speed.multiplier()),
+                            TextStyle {
+                                font: asset_server.load("FiraSans-Bold.ttf"),
+                                font_size: 28.0,
+                                color: Color::BLACK,
+                            },
+                        ));
+                    });
+            }
+        });
+}
@@ -0,0 +1,159 @@
+use std::{fmt, time::Duration};
+
+use bevy::{
+    asset::AssetPlugin, hierarchy::HierarchyPlugin, input::InputPlugin, prelude::*,
+    scene::ScenePlugin, time::TimePlugin, transform::TransformPlugin,
+};
+use bevy_rapier3d::prelude::{NoUserData, RapierPhysicsPlugin};
+
+use crate::components::{GameAssets, GameState, TowerType};
+use crate::level::{Level, TowerLayout};
+use crate::player::Player;
+use crate::stats::MatchStats;
+use crate::GamePlugin;
+
+/// How much game time passes on every update of a headless app
+#[derive(Resource)]
+pub struct SimulationClock {
+    pub step: Duration,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self {
+            step: Duration::from_secs_f64(1.0 / 60.0),
+        }
+    }
+}
+
+/// Moves `Time` forward by a fixed step instead of following the wall clock,
+/// so simulations run as fast as possible and always play out the same way
+fn advance_clock(mut time: ResMut<Time>, clock: Res<SimulationClock>) {
+    let last_update = time.last_update().unwrap_or_else(|| time.startup());
+    time.update_with_instant(last_update + clock.step);
+}
+
+/// Builds an app that plays `level` with the towers in `layout` already built,
+/// without a window, rendering or sound output
+pub fn headless_app(level: Level, layout: TowerLayout) -> App {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins.build().disable::<TimePlugin>())
+        .init_resource::<Time>()
+        .init_resource::<SimulationClock>()
+        .add_system_to_stage(CoreStage::First, advance_clock)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(ScenePlugin)
+        .add_asset::<Mesh>()
+        .add_asset::<StandardMaterial>()
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+        // Nothing is drawn, so stub handles stand in for the models
+        .insert_resource(GameAssets::default())
+        .insert_resource(level)
+        .insert_resource(layout)
+        .add_state(GameState::InGame)
+        .add_plugin(GamePlugin);
+
+    app
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchResult {
+    /// Every wave was sent and no targets are left
+    Victory,
+    Defeat,
+    /// The time limit ran out first
+    TimedOut,
+}
+
+impl fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatchResult::Victory => write!(f, "victory"),
+            MatchResult::Defeat => write!(f, "defeat"),
+            MatchResult::TimedOut => write!(f, "timed out"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub result: MatchResult,
+    pub health: u32,
+    pub money: u32,
+    /// Game time the match lasted
+    pub duration: Duration,
+    pub stats: MatchStats,
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "result: {}", self.result)?;
+        writeln!(f, "health: {}", self.health)?;
+        writeln!(f, "money: {}", self.money)?;
+        writeln!(f, "duration: {:.1}s", self.duration.as_secs_f32())?;
+        writeln!(f, "kills: {}", self.stats.kills)?;
+        writeln!(f, "lives lost: {}", self.stats.lives_lost)?;
+        write!(f, "money earned: {}", self.stats.money_earned)?;
+
+        for tower_type in TowerType::ALL {
+            let stats = self.stats.tower_type(tower_type);
+            write!(
+                f,
+                "\n{:?}: {} shots, {:.0}% hit rate, {} damage, {} kills",
+                tower_type,
+                stats.shots,
+                stats.hit_rate() * 100.0,
+                stats.damage,
+                stats.kills
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Steps `app` until the match is decided or `time_limit` of game time has passed
+pub fn run_simulation(app: &mut App, time_limit: Duration) -> SimulationReport {
+    let step = app.world.resource::<SimulationClock>().step;
+    let mut duration = Duration::ZERO;
+
+    let result = loop {
+        app.update();
+        duration += step;
+
+        if let Some(result) = match_result(&app.world) {
+            break result;
+        }
+        if duration >= time_limit {
+            break MatchResult::TimedOut;
+        }
+    };
+
+    let stats = app.world.resource::<MatchStats>().clone();
+    // Once the match is over the player is gone and only the stats remember it
+    let (health, money) = app
+        .world
+        .query::<&Player>()
+        .get_single(&app.world)
+        .map(|player| (player.health, player.money))
+        .unwrap_or((stats.lives_left, stats.money_left));
+
+    SimulationReport {
+        result,
+        health,
+        money,
+        duration,
+        stats,
+    }
+}
+
+fn match_result(world: &World) -> Option<MatchResult> {
+    match world.resource::<State<GameState>>().current() {
+        GameState::GameOver => Some(MatchResult::Defeat),
+        GameState::Victory => Some(MatchResult::Victory),
+        _ => None,
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::BTreeMap, fs, path::Path, path::PathBuf};
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+use serde::{Deserialize, Serialize};
+
+use crate::components::GameState;
+use crate::level::Level;
+use crate::localization::LocalizedText;
+use crate::stats::MatchStats;
+
+pub const HIGH_SCORES_FILE: &str = "highscores.ron";
+/// Scores kept for each level
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 16;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+}
+
+/// The best scores on each level, by level name, highest first
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct HighScores {
+    pub levels: BTreeMap<String, Vec<HighScore>>,
+}
+
+impl HighScores {
+    /// Reads the high score file, starting a new table when it is missing or broken
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!(
+                    "ignoring invalid high scores in {}: {}",
+                    path.display(),
+                    err
+                );
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        fs::write(path, contents)
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))
+    }
+
+    pub fn level(&self, level: &str) -> &[HighScore] {
+        self.levels.get(level).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether `score` would make it onto the table for `level`
+    pub fn qualifies(&self, level: &str, score: u32) -> bool {
+        let scores = self.level(level);
+        scores.len() < MAX_HIGH_SCORES || scores.iter().any(|entry| score > entry.score)
+    }
+
+    /// Adds a score to the table for `level`, returning its place from zero if it made it on.
+    /// Ties go below the scores that were there first.
+    pub fn insert(&mut self, level: &str, entry: HighScore) -> Option<usize> {
+        let scores = self.levels.entry(level.to_string()).or_default();
+        let rank = scores.partition_point(|existing| existing.score >= entry.score);
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        scores.insert(rank, entry);
+        scores.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+}
+
+/// Set by the game binary, high scores aren't saved without it
+#[derive(Resource)]
+pub struct HighScoresPath(pub PathBuf);
+
+/// The name being typed for a score that made the table, until it's entered
+#[derive(Resource, Default, Debug)]
+pub struct NameEntry {
+    /// `None` once entered, or when the score didn't make it
+    pub name: Option<String>,
+    /// Place on the table of the score from the match just played
+    pub rank: Option<usize>,
+}
+
+/// Filled in by the end screen with the high score table and the name prompt
+#[derive(Component)]
+pub struct HighScoreTable;
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        // Normally from the window plugin, headless apps type names too
+        app.add_event::<ReceivedCharacter>()
+            .init_resource::<HighScores>()
+            .init_resource::<NameEntry>();
+
+        for state in [GameState::GameOver, GameState::Victory] {
+            app.add_system_set(SystemSet::on_enter(state.clone()).with_system(start_name_entry))
+                .add_system_set(
+                    SystemSet::on_update(state)
+                        .with_system(type_name)
+                        .with_system(update_high_score_table.after(type_name)),
+                );
+        }
+    }
+}
+
+fn start_name_entry(
+    mut entry: ResMut<NameEntry>,
+    high_scores: Res<HighScores>,
+    stats: Res<MatchStats>,
+    level: Res<Level>,
+) {
+    *entry = NameEntry::default();
+    if high_scores.qualifies(&level.name, stats.score()) {
+        entry.name = Some(String::new());
+    }
+}
+
+fn type_name(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard: Res<Input<KeyCode>>,
+    mut entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+    path: Option<Res<HighScoresPath>>,
+    stats: Res<MatchStats>,
+    level: Res<Level>,
+) {
+    let Some(name) = entry.name.as_ref() else {
+        return;
+    };
+
+    let mut typed = name.clone();
+    for character in characters.iter() {
+        if !character.char.is_control() && typed.chars().count() < MAX_NAME_LENGTH {
+            typed.push(character.char);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        typed.pop();
+    }
+
+    let submitted = keyboard.just_pressed(KeyCode::Return) && !typed.trim().is_empty();
+    if !submitted {
+        if typed != *name {
+            entry.name = Some(typed);
+        }
+        return;
+    }
+
+    entry.name = None;
+    entry.rank = high_scores.insert(
+        &level.name,
+        HighScore {
+            name: typed.trim().to_string(),
+            score: stats.score(),
+        },
+    );
+
+    if let Some(path) = path {
+        if let Err(err) = high_scores.save(&path.0) {
+            warn!("{}", err);
+        }
+    }
+}
+
+/// Lists the level's high scores, with the name prompt above them while it's open
+fn update_high_score_table(
+    mut commands: Commands,
+    tables: Query<(Entity, ChangeTrackers<HighScoreTable>), With<HighScoreTable>>,
+    entry: Res<NameEntry>,
+    high_scores: Res<HighScores>,
+    level: Res<Level>,
+    asset_server: Res<AssetServer>,
+) {
+    for (table, tracker) in &tables {
+        if !tracker.is_added() && !entry.is_changed() && !high_scores.is_changed() {
+            continue;
+        }
+
+        let line = |text: LocalizedText, color: Color| {
+            (
+                TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 28.0,
+                            color,
+                        },
+                    ),
+                    ..default()
+                },
+                text,
+            )
+        };
+
+        commands.entity(table).despawn_descendants();
+        commands.entity(table).with_children(|commands| {
+            if let Some(name) = &entry.name {
+                commands.spawn(line(
+                    LocalizedText::new("high-score-enter-name").with_arg("name", name),
+                    Color::RED,
+                ));
+            }
+
+            commands.spawn(line(LocalizedText::new("high-score-title"), Color::BLACK));
+            for (rank, score) in high_scores.level(&level.name).iter().enumerate() {
+                // The score just entered stands out
+                let color = if entry.rank == Some(rank) {
+                    Color::RED
+                } else {
+                    Color::BLACK
+                };
+                commands.spawn(line(
+                    LocalizedText::new("high-score-entry")
+                        .with_arg("rank", rank + 1)
+                        .with_arg("name", &score.name)
+                        .with_arg("score", score.score),
+                    color,
+                ));
+            }
+        });
+    }
+}
@@ -0,0 +1,143 @@
+use std::{fs, path::Path, str::FromStr};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::build_grid::BuildGrid;
+use crate::components::TowerType;
+use crate::spline::PathSpline;
+
+/// Describes a map: the floor, where towers can go, the route targets walk and the waves sent down it.
+///
+/// Positions are on the ground plane, so `Vec2::x` is world x and `Vec2::y` is world z.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Level {
+    pub name: String,
+    pub floor_size: f32,
+    /// Where each wave lines up before walking the path
+    pub spawn: Vec2,
+    pub waypoints: Vec<Vec2>,
+    pub tower_slots: Vec<Vec2>,
+    pub waves: Vec<Wave>,
+    /// Seconds to wait after a wave is cleared before sending the next one
+    pub wave_delay: f32,
+    /// Lets towers be placed freely on the floor, on top of the fixed slots
+    #[serde(default)]
+    pub build_grid: Option<BuildGrid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Wave {
+    pub count: u32,
+    pub health: i32,
+    pub speed: f32,
+    /// Distance between targets in the line behind the spawn
+    pub spacing: f32,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        let mut tower_slots = Vec::new();
+        for i in 0..10 {
+            for j in 0..2 {
+                tower_slots.push(Vec2::new(2.0 * i as f32 + j as f32, 5.0 * j as f32));
+            }
+        }
+
+        Self {
+            name: "Tutorial".to_string(),
+            floor_size: 50.0,
+            spawn: Vec2::new(0.0, 2.5),
+            waypoints: vec![
+                Vec2::new(6.0, 2.0),
+                Vec2::new(6.0, 6.0),
+                Vec2::new(9.0, 9.0),
+            ],
+            tower_slots,
+            waves: vec![Wave {
+                count: 24,
+                health: 3,
+                speed: 0.45,
+                spacing: 2.0,
+            }],
+            wave_delay: 5.0,
+            build_grid: None,
+        }
+    }
+}
+
+impl Level {
+    /// Reads a level from a RON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+        ron::from_str(&contents)
+            .map_err(|err| format!("failed to parse {}: {}", path.display(), err))
+    }
+
+    /// Writes the level as RON, the format `load` reads
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        fs::write(path, contents)
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))
+    }
+
+    /// Where targets leave the level, the last waypoint
+    pub fn exit(&self) -> Vec2 {
+        self.waypoints.last().copied().unwrap_or(self.spawn)
+    }
+
+    /// Points targets walk between, from the spawn to the last waypoint
+    pub fn path(&self) -> impl Iterator<Item = Vec2> + '_ {
+        std::iter::once(self.spawn).chain(self.waypoints.iter().copied())
+    }
+
+    /// Distance from `point` to the nearest part of the path, following the curve targets walk
+    /// rather than the straight lines between the points
+    pub fn distance_to_path(&self, point: Vec2) -> f32 {
+        PathSpline::new(self.path()).distance_to(point)
+    }
+}
+
+/// Towers that are already built when the match starts, as `(slot index, tower type)` pairs
+#[derive(Resource, Default, Clone, Debug)]
+pub struct TowerLayout {
+    pub towers: Vec<(usize, TowerType)>,
+}
+
+impl TowerLayout {
+    pub fn tower_at(&self, slot: usize) -> Option<TowerType> {
+        self.towers
+            .iter()
+            .find(|(tower_slot, _)| *tower_slot == slot)
+            .map(|(_, tower_type)| *tower_type)
+    }
+}
+
+/// Parses layouts written as `slot=type` pairs, e.g. `0=tomato,3=cabbage`
+impl FromStr for TowerLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let towers = s
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (slot, tower_type) = entry
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected slot=type, got '{}'", entry))?;
+                let slot = slot
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid slot '{}'", slot))?;
+                Ok((slot, tower_type.trim().parse()?))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self { towers })
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_rapier3d::prelude::RapierConfiguration;
+
+mod audio;
+mod build_grid;
+mod bullet;
+mod camera;
+pub mod components;
+mod controls;
+mod difficulty;
+mod editor;
+mod end_screen;
+mod game_speed;
+pub mod headless;
+mod high_scores;
+pub mod level;
+mod localization;
+mod main_menu;
+mod minimap;
+mod path_view;
+mod pathfinding;
+mod pause_menu;
+mod physics;
+mod player;
+mod settings;
+mod spline;
+mod state_scoped;
+mod stats;
+mod target;
+mod tower;
+mod tower_panel;
+mod wave;
+
+pub use audio::*;
+pub use build_grid::*;
+pub use bullet::*;
+pub use camera::*;
+pub use controls::*;
+pub use difficulty::*;
+pub use editor::*;
+pub use end_screen::*;
+pub use game_speed::*;
+pub use high_scores::*;
+pub use level::*;
+pub use localization::*;
+pub use main_menu::*;
+pub use minimap::*;
+pub use path_view::*;
+pub use pathfinding::*;
+pub use pause_menu::*;
+use physics::PhysicsPlugin;
+pub use player::*;
+pub use settings::*;
+pub use spline::*;
+pub use state_scoped::*;
+pub use stats::*;
+pub use target::*;
+pub use tower::*;
+pub use tower_panel::*;
+pub use wave::*;
+
+pub const HEIGHT: f32 = 720.0;
+pub const WIDTH: f32 = 1280.0;
+
+use crate::components::{GameAssets, GameState};
+
+/// Everything needed to play a match, without any windowing or rendering.
+///
+/// The caller is responsible for adding the `GameState` and the `GameAssets` resource.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Level>()
+            .init_resource::<TowerLayout>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(spawn_scene))
+            .add_plugin(StateScopedPlugin::new([
+                GameState::MainMenu,
+                GameState::InGame,
+                GameState::Paused,
+                GameState::GameOver,
+                GameState::Victory,
+                GameState::Settings,
+                GameState::Controls,
+                GameState::Editor,
+            ]))
+            .add_plugin(ControlsPlugin)
+            .add_plugin(RtsCameraPlugin)
+            .add_plugin(MinimapPlugin)
+            .add_plugin(DifficultyPlugin)
+            .add_plugin(BulletPlugin)
+            .add_plugin(TowerPlugin)
+            .add_plugin(TowerPanelPlugin)
+            .add_plugin(BuildGridPlugin)
+            .add_plugin(PathfindingPlugin)
+            .add_plugin(PathViewPlugin)
+            .add_plugin(LevelEditorPlugin)
+            .add_plugin(TargetPlugin)
+            .add_plugin(WavePlugin)
+            .add_plugin(PhysicsPlugin)
+            .add_plugin(PlayerPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(PauseMenuPlugin)
+            .add_plugin(GameSpeedPlugin)
+            .add_plugin(EndScreenPlugin)
+            .add_plugin(HighScoresPlugin)
+            .add_plugin(SettingsPlugin)
+            .add_plugin(AudioManagerPlugin)
+            .add_plugin(LocalizationPlugin);
+    }
+}
+
+fn spawn_scene(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    game_assets: Res<GameAssets>,
+    level: Res<Level>,
+    layout: Res<TowerLayout>,
+    picking: Res<TowerPickingAssets>,
+) {
+    rapier_config.gravity = Vec3::ZERO;
+
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Plane {
+                size: level.floor_size,
+            })),
+            material: materials.add(Color::rgb(0.2, 1.0, 0.2).into()),
+            // transform: Transform::from_xyz(0.0, 0.0, 0.0),
+            ..Default::default()
+        })
+        .insert(Name::new("Floor"))
+        .insert(StateScoped(GameState::InGame));
+
+    // spawn light
+
+    commands
+        .spawn(PointLightBundle {
+            point_light: PointLight {
+                intensity: 1500.0,
+                shadows_enabled: true,
+                ..default()
+            },
+            transform: Transform::from_xyz(4.0, 8.0, 4.0),
+            ..default()
+        })
+        .insert(Name::new("Light"))
+        .insert(StateScoped(GameState::InGame));
+
+    for (slot, position) in level.tower_slots.iter().enumerate() {
+        let translation = position.extend(TOWER_BASE_HEIGHT).xzy();
+
+        // Slots taken by the starting layout get their tower straight away
+        match layout.tower_at(slot) {
+            Some(tower_type) => spawn_tower(
+                &mut commands,
+                &game_assets,
+                &picking,
+                translation,
+                tower_type,
+            ),
+            None => spawn_tower_base(&mut commands, &game_assets, &picking, translation),
+        };
+    }
+}
+
+pub fn asset_loading(mut commands: Commands, assets: Res<AssetServer>) {
+    commands.insert_resource(GameAssets {
+        tower_base_scene: assets.load("TowerBase.glb#Scene0"),
+        tomato_tower_scene: assets.load("TomatoTower.glb#Scene0"),
+        tomato_scene: assets.load("Tomato.glb#Scene0"),
+        potato_tower_scene: assets.load("PotatoTower.glb#Scene0"),
+        potato_scene: assets.load("Potato.glb#Scene0"),
+        cabbage_tower_scene: assets.load("CabbageTower.glb#Scene0"),
+        cabbage_scene: assets.load("Cabbage.glb#Scene0"),
+        target_scene: assets.load("Target.glb#Scene0"),
+    });
+}
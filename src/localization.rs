@@ -0,0 +1,254 @@
+use std::{fs, path::Path};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// Folder with one `<code>.ftl` string table per language, inside the assets folder
+pub const LOCALES_DIR: &str = "locales";
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    /// Name of the language in itself, so it can be found from any other language
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+}
+
+/// Messages of one language, read from a subset of the Fluent format:
+///
+/// ```text
+/// # Comment
+/// hud-money = Money: { $money }
+/// ```
+///
+/// Indented lines continue the message above them.
+#[derive(TypeUuid, Default, Clone, Debug)]
+#[uuid = "a3f1c9d4-2b7e-4e85-9c06-71d5e8b2f4a9"]
+pub struct StringTable {
+    messages: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut messages = HashMap::default();
+        let mut last_key: Option<String> = None;
+
+        for (number, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with(char::is_whitespace) {
+                let message: &mut String = last_key
+                    .as_ref()
+                    .and_then(|key| messages.get_mut(key))
+                    .ok_or_else(|| {
+                        format!("line {}: continuation without a message", number + 1)
+                    })?;
+                message.push('\n');
+                message.push_str(trimmed);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected 'key = value'", number + 1))?;
+            let key = key.trim().to_string();
+            messages.insert(key.clone(), value.trim().to_string());
+            last_key = Some(key);
+        }
+
+        Ok(Self { messages })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+        Self::parse(&contents).map_err(|err| format!("failed to parse {}: {}", path.display(), err))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+}
+
+/// Reads `.ftl` files for the asset server
+#[derive(Default)]
+struct StringTableLoader;
+
+impl AssetLoader for StringTableLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let source = std::str::from_utf8(bytes)?;
+            let table = StringTable::parse(source).map_err(bevy::asset::Error::msg)?;
+            load_context.set_default_asset(LoadedAsset::new(table));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ftl"]
+    }
+}
+
+/// Every language's table, loaded at startup so switching languages doesn't wait on the disk
+#[derive(Resource)]
+struct StringTableHandles(HashMap<Language, Handle<StringTable>>);
+
+/// The string tables for the language in the settings, with English filling any gaps
+#[derive(Resource, Default)]
+pub struct Localization {
+    /// `None` until the first table is loaded
+    language: Option<Language>,
+    strings: StringTable,
+    fallback: StringTable,
+}
+
+impl Localization {
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    /// The message for `key` with its `{ $name }` placeables filled from `args`,
+    /// or the key itself when no table has it
+    pub fn format(&self, key: &str, args: &[(String, String)]) -> String {
+        let Some(message) = self.strings.get(key).or_else(|| self.fallback.get(key)) else {
+            return key.to_string();
+        };
+
+        args.iter()
+            .fold(message.to_string(), |message, (name, value)| {
+                message.replace(&format!("{{ ${} }}", name), value)
+            })
+    }
+
+    pub fn get(&self, key: &str) -> String {
+        self.format(key, &[])
+    }
+}
+
+/// Keeps the first section of a `Text` showing a message in the current language
+#[derive(Component, Clone, Debug, Default, PartialEq)]
+pub struct LocalizedText {
+    pub key: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl LocalizedText {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.args.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<StringTable>()
+            .init_asset_loader::<StringTableLoader>()
+            .init_resource::<Localization>()
+            .add_startup_system(load_string_tables)
+            .add_system_to_stage(CoreStage::PreUpdate, switch_language)
+            // After Update so text spawned this frame is filled in before it is drawn
+            .add_system_to_stage(CoreStage::PostUpdate, update_localized_text);
+    }
+}
+
+fn load_string_tables(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = Language::ALL
+        .into_iter()
+        .map(|language| {
+            let path = format!("{}/{}.ftl", LOCALES_DIR, language.code());
+            (language, asset_server.load(path))
+        })
+        .collect();
+    commands.insert_resource(StringTableHandles(handles));
+}
+
+/// Switches to the language in the settings once its table and the English one are loaded
+fn switch_language(
+    settings: Res<Settings>,
+    handles: Option<Res<StringTableHandles>>,
+    tables: Res<Assets<StringTable>>,
+    mut events: EventReader<AssetEvent<StringTable>>,
+    mut localization: ResMut<Localization>,
+) {
+    // A table that was waited on may have come in
+    let loaded = events.iter().count() > 0;
+    if localization.language == Some(settings.language) && !loaded {
+        return;
+    }
+    let Some(handles) = handles else {
+        return;
+    };
+
+    let table = |language: Language| {
+        handles
+            .0
+            .get(&language)
+            .and_then(|handle| tables.get(handle))
+            .cloned()
+    };
+    let (Some(strings), Some(fallback)) = (table(settings.language), table(Language::English))
+    else {
+        return;
+    };
+
+    localization.strings = strings;
+    localization.fallback = fallback;
+    localization.language = Some(settings.language);
+}
+
+fn update_localized_text(
+    localization: Res<Localization>,
+    mut texts: Query<(&mut Text, &LocalizedText, ChangeTrackers<LocalizedText>)>,
+) {
+    for (mut text, localized, tracker) in &mut texts {
+        if localization.is_changed() || tracker.is_changed() {
+            text.sections[0].value = localization.format(&localized.key, &localized.args);
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use bevy::{app::AppExit, prelude::*};
 
+use crate::difficulty::spawn_difficulty_buttons;
+use crate::editor::EditorButton;
+use crate::localization::LocalizedText;
+use crate::settings::SettingsButton;
+use crate::state_scoped::StateScoped;
 use crate::GameState;
 
 #[derive(Component)]
@@ -26,17 +31,12 @@ impl Plugin for MainMenuPlugin {
 }
 
 fn start_button_clicked(
-    mut commands: Commands,
     interactions: Query<&Interaction, (With<StartButton>, Changed<Interaction>)>,
-    menu_root: Query<Entity, With<MenuUIRoot>>,
     mut game_state: ResMut<State<GameState>>,
     mut mouse_input: ResMut<Input<MouseButton>>,
 ) {
     for interaction in &interactions {
         if matches!(interaction, Interaction::Clicked) {
-            let root_entity = menu_root.single();
-            commands.entity(root_entity).despawn_recursive();
-
             game_state.set(GameState::InGame).unwrap();
             mouse_input.clear();
         }
@@ -55,10 +55,19 @@ fn quit_button_clicked(
 }
 
 fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let start_button = spawn_button(&mut commands, &asset_server, "Start Game", Color::RED);
+    let start_button = spawn_button(&mut commands, &asset_server, "menu-start", Color::RED);
     commands.entity(start_button).insert(StartButton);
 
-    let quit_button = spawn_button(&mut commands, &asset_server, "Quit", Color::BLUE);
+    let difficulty_buttons = spawn_difficulty_buttons(&mut commands, &asset_server);
+
+    let editor_button = spawn_button(&mut commands, &asset_server, "menu-editor", Color::GRAY);
+    commands.entity(editor_button).insert(EditorButton);
+
+    let settings_button =
+        spawn_button(&mut commands, &asset_server, "button-settings", Color::GRAY);
+    commands.entity(settings_button).insert(SettingsButton);
+
+    let quit_button = spawn_button(&mut commands, &asset_server, "menu-quit", Color::BLUE);
     commands.entity(quit_button).insert(QuitButton);
 
     commands
@@ -72,32 +81,39 @@ fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         })
         .insert(MenuUIRoot)
+        .insert(StateScoped(GameState::MainMenu))
         .with_children(|commands| {
-            commands.spawn(TextBundle {
-                style: Style {
-                    align_self: AlignSelf::Center,
-                    margin: UiRect::all(Val::Percent(3.0)),
-                    ..default()
-                },
-                text: Text::from_section(
-                    "Tower Defense Tutorial",
-                    TextStyle {
-                        font: asset_server.load("FiraSans-Bold.ttf"),
-                        font_size: 96.0,
-                        color: Color::BLACK,
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Percent(3.0)),
+                        ..default()
                     },
-                ),
-                ..default()
-            });
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 96.0,
+                            color: Color::BLACK,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(LocalizedText::new("menu-title"));
         })
         .add_child(start_button)
+        .add_child(difficulty_buttons)
+        .add_child(editor_button)
+        .add_child(settings_button)
         .add_child(quit_button);
 }
 
-fn spawn_button(
+/// A big menu button showing the message `text_key`
+pub(crate) fn spawn_button(
     commands: &mut Commands,
     asset_server: &AssetServer,
-    text: &str,
+    text_key: &str,
     color: Color,
 ) -> Entity {
     commands
@@ -113,22 +129,24 @@ fn spawn_button(
             ..default()
         })
         .with_children(|commands| {
-            commands.spawn(TextBundle {
-                style: Style {
-                    align_self: AlignSelf::Center,
-                    margin: UiRect::all(Val::Percent(3.0)),
-                    ..default()
-                },
-                text: Text::from_section(
-                    text,
-                    TextStyle {
-                        font: asset_server.load("FiraSans-Bold.ttf"),
-                        font_size: 64.0,
-                        color: Color::BLACK,
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Percent(3.0)),
+                        ..default()
                     },
-                ),
-                ..default()
-            });
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 64.0,
+                            color: Color::BLACK,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(LocalizedText::new(text_key));
         })
         .id()
 }
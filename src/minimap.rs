@@ -0,0 +1,207 @@
+use bevy::{
+    math::Vec3Swizzles, prelude::*, render::camera::ScalingMode, ui::FocusPolicy, utils::HashSet,
+};
+
+use crate::camera::RtsCamera;
+use crate::components::{GameState, Target, Tower};
+use crate::level::Level;
+use crate::state_scoped::StateScoped;
+
+/// Width and height of the minimap image, in pixels of both the render target and the HUD
+pub const MINIMAP_SIZE: f32 = 256.0;
+/// Gap between the minimap and the bottom left window corner, the game speed buttons take the
+/// bottom right one
+const MINIMAP_MARGIN: f32 = 12.0;
+const MARKER_SIZE: f32 = 6.0;
+/// Height the minimap camera looks down from, above everything in the level
+const MINIMAP_CAMERA_HEIGHT: f32 = 50.0;
+
+/// The render target the minimap camera draws into, set by the game binary
+#[derive(Resource)]
+pub struct MinimapImage(pub Handle<Image>);
+
+/// Orthographic camera looking straight down at the level, rendering the minimap
+#[derive(Component)]
+pub struct MinimapCamera;
+
+/// The minimap on the HUD, clicking it moves the main camera
+#[derive(Component)]
+pub struct Minimap;
+
+/// A dot on the minimap following a target or tower
+#[derive(Component)]
+pub struct MinimapMarker(pub Entity);
+
+/// The ground point shown at `uv` on the minimap, where `uv` goes from (0, 0) at the
+/// bottom left corner to (1, 1) at the top right.
+///
+/// The minimap is drawn looking down with world +z at the top, which puts world -x on
+/// the right, the same way round as the main camera starts.
+pub fn minimap_to_world(uv: Vec2, floor_size: f32) -> Vec2 {
+    Vec2::new(0.5 - uv.x, uv.y - 0.5) * floor_size
+}
+
+pub fn world_to_minimap(position: Vec2, floor_size: f32) -> Vec2 {
+    Vec2::new(0.5 - position.x / floor_size, position.y / floor_size + 0.5)
+}
+
+/// The minimap camera's transform, it is kept still and sized to the level instead
+pub fn minimap_camera_transform() -> Transform {
+    Transform::from_xyz(0.0, MINIMAP_CAMERA_HEIGHT, 0.0).looking_at(Vec3::ZERO, Vec3::Z)
+}
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::InGame)
+                .with_system(fit_minimap_camera)
+                .with_system(spawn_minimap),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(update_minimap_markers)
+                .with_system(minimap_clicked),
+        );
+    }
+}
+
+fn fit_minimap_camera(level: Res<Level>, mut cameras: Query<&mut Projection, With<MinimapCamera>>) {
+    for mut projection in &mut cameras {
+        if let Projection::Orthographic(orthographic) = projection.as_mut() {
+            orthographic.scaling_mode = ScalingMode::FixedVertical(level.floor_size);
+        }
+    }
+}
+
+fn spawn_minimap(mut commands: Commands, image: Option<Res<MinimapImage>>) {
+    commands
+        .spawn(ImageBundle {
+            style: Style {
+                size: Size::new(Val::Px(MINIMAP_SIZE), Val::Px(MINIMAP_SIZE)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(MINIMAP_MARGIN),
+                    bottom: Val::Px(MINIMAP_MARGIN),
+                    ..default()
+                },
+                ..default()
+            },
+            image: image
+                .map(|image| UiImage(image.0.clone()))
+                .unwrap_or_default(),
+            // Clicks on the minimap shouldn't also pick what's under it in the world
+            focus_policy: FocusPolicy::Block,
+            ..default()
+        })
+        .insert(Interaction::default())
+        .insert(Minimap)
+        .insert(Name::new("Minimap"))
+        .insert(StateScoped(GameState::InGame));
+}
+
+fn update_minimap_markers(
+    mut commands: Commands,
+    minimap: Query<Entity, With<Minimap>>,
+    tracked: Query<(Entity, &GlobalTransform, Option<&Target>), Or<(With<Target>, With<Tower>)>>,
+    mut markers: Query<(Entity, &MinimapMarker, &mut Style)>,
+    level: Res<Level>,
+) {
+    let Ok(minimap) = minimap.get_single() else {
+        return;
+    };
+
+    let marker_layout = |transform: &GlobalTransform| {
+        let uv = world_to_minimap(transform.translation().xz(), level.floor_size);
+        let offset = MARKER_SIZE / 2.0;
+        let position = UiRect {
+            left: Val::Px(uv.x * MINIMAP_SIZE - offset),
+            bottom: Val::Px(uv.y * MINIMAP_SIZE - offset),
+            ..default()
+        };
+        // Targets lined up behind the spawn can be off the floor, and so off the minimap
+        let display = if uv.cmpge(Vec2::ZERO).all() && uv.cmple(Vec2::ONE).all() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        (position, display)
+    };
+
+    let mut marked = HashSet::default();
+    for (marker_entity, marker, mut style) in &mut markers {
+        match tracked.get(marker.0) {
+            Ok((_, transform, _)) => {
+                let (position, display) = marker_layout(transform);
+                if style.position != position || style.display != display {
+                    style.position = position;
+                    style.display = display;
+                }
+                marked.insert(marker.0);
+            }
+            Err(_) => commands.entity(marker_entity).despawn_recursive(),
+        }
+    }
+
+    for (entity, transform, target) in &tracked {
+        if marked.contains(&entity) {
+            continue;
+        }
+
+        let color = if target.is_some() {
+            Color::RED
+        } else {
+            Color::BLUE
+        };
+        let (position, display) = marker_layout(transform);
+        let marker = commands
+            .spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(MARKER_SIZE), Val::Px(MARKER_SIZE)),
+                    position_type: PositionType::Absolute,
+                    position,
+                    display,
+                    ..default()
+                },
+                background_color: color.into(),
+                focus_policy: FocusPolicy::Pass,
+                ..default()
+            })
+            .insert(MinimapMarker(entity))
+            .id();
+        commands.entity(minimap).add_child(marker);
+    }
+}
+
+/// Moves the main camera to the clicked point, holding the button down drags it around
+fn minimap_clicked(
+    minimap: Query<&Interaction, With<Minimap>>,
+    windows: Option<Res<Windows>>,
+    ui_scale: Option<Res<UiScale>>,
+    level: Res<Level>,
+    mut cameras: Query<&mut RtsCamera>,
+) {
+    if !minimap
+        .iter()
+        .any(|interaction| matches!(interaction, Interaction::Clicked))
+    {
+        return;
+    }
+
+    let Some(window) = windows.as_ref().and_then(|windows| windows.get_primary()) else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    // The minimap sits in the bottom left corner, where the cursor is measured from
+    let scale = ui_scale.map_or(1.0, |ui_scale| ui_scale.scale as f32);
+    let corner = Vec2::splat(MINIMAP_MARGIN * scale);
+    let uv = ((cursor - corner) / (MINIMAP_SIZE * scale)).clamp(Vec2::ZERO, Vec2::ONE);
+
+    for mut camera in &mut cameras {
+        camera.focus = minimap_to_world(uv, level.floor_size);
+    }
+}
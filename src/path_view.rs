@@ -0,0 +1,232 @@
+use bevy::{
+    math::Vec3Swizzles,
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::components::GameState;
+use crate::level::Level;
+use crate::spline::PathSpline;
+use crate::state_scoped::StateScoped;
+
+/// Width of the road drawn along the path
+pub const ROAD_WIDTH: f32 = 1.0;
+/// Length of road between two cross sections of its mesh
+const ROAD_RESOLUTION: f32 = 0.25;
+/// Height above the floor the road lies at, so it isn't hidden in it
+const ROAD_HEIGHT: f32 = 0.01;
+/// Distance between the arrows moving along the road
+const ARROW_SPACING: f32 = 2.0;
+/// Distance the arrows move along the road per second
+const ARROW_SPEED: f32 = 1.0;
+const MARKER_RADIUS: f32 = 0.6;
+
+/// The road strip showing the route targets walk
+#[derive(Component)]
+pub struct PathRoad;
+
+/// An arrow sliding along the road towards the exit
+#[derive(Component)]
+pub struct PathArrow {
+    /// Distance along the path the arrow started at
+    pub offset: f32,
+}
+
+/// Disc on the floor at either end of the path
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathMarker {
+    Spawn,
+    Exit,
+}
+
+/// A flat arrow pointing along +z, a little above the floor
+pub fn flat_arrow_mesh() -> Mesh {
+    let y = 0.02;
+    let positions = vec![
+        [-0.06, y, -0.35],
+        [0.06, y, -0.35],
+        [0.06, y, 0.1],
+        [-0.06, y, 0.1],
+        [-0.2, y, 0.1],
+        [0.2, y, 0.1],
+        [0.0, y, 0.35],
+    ];
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 1.0, 0.0]; positions.len()],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; positions.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(vec![0, 2, 1, 0, 3, 2, 4, 6, 5])));
+    mesh
+}
+
+/// A flat strip `width` wide following `spline` on the floor.
+///
+/// The texture runs across the strip with `u` and repeats along it with `v`, once per `width`.
+pub fn road_mesh(spline: &PathSpline, width: f32) -> Mesh {
+    let sections = (spline.length() / ROAD_RESOLUTION).ceil().max(1.0) as usize;
+    let half_width = width / 2.0;
+
+    let mut positions = Vec::with_capacity((sections + 1) * 2);
+    let mut uvs = Vec::with_capacity((sections + 1) * 2);
+    for section in 0..=sections {
+        let distance = spline.length() * section as f32 / sections as f32;
+        let center = spline.point_at(distance);
+        // Right of the direction of travel, seen from above
+        let side = spline.tangent_at(distance).perp() * half_width;
+
+        for (point, u) in [(center + side, 0.0), (center - side, 1.0)] {
+            positions.push([point.x, ROAD_HEIGHT, point.y]);
+            uvs.push([u, distance / width]);
+        }
+    }
+
+    let mut indices = Vec::with_capacity(sections * 6);
+    for section in 0..sections as u32 {
+        let (left, right) = (section * 2, section * 2 + 1);
+        let (next_left, next_right) = (left + 2, right + 2);
+        indices.extend([left, next_left, right, right, next_left, next_right]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 1.0, 0.0]; positions.len()],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Where an arrow that started at `offset` is after `seconds`, looping back to the spawn
+pub fn arrow_distance(offset: f32, seconds: f32, path_length: f32) -> f32 {
+    if path_length <= 0.0 {
+        return 0.0;
+    }
+    (offset + seconds * ARROW_SPEED).rem_euclid(path_length)
+}
+
+pub struct PathViewPlugin;
+
+impl Plugin for PathViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(spawn_path_view))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(move_path_arrows));
+    }
+}
+
+/// The path shown on the floor, the spline the targets follow
+#[derive(Resource)]
+struct PathView {
+    spline: PathSpline,
+    started: f32,
+}
+
+fn spawn_path_view(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    level: Res<Level>,
+    time: Res<Time>,
+) {
+    let spline = PathSpline::new(level.path());
+
+    for (marker, position, color) in [
+        (PathMarker::Spawn, level.spawn, Color::rgb(0.2, 0.4, 1.0)),
+        (PathMarker::Exit, level.exit(), Color::rgb(1.0, 0.2, 0.2)),
+    ] {
+        commands
+            .spawn(PbrBundle {
+                mesh: meshes.add(
+                    shape::Cylinder {
+                        radius: MARKER_RADIUS,
+                        height: 0.05,
+                        ..default()
+                    }
+                    .into(),
+                ),
+                material: materials.add(StandardMaterial {
+                    base_color: color,
+                    unlit: true,
+                    ..default()
+                }),
+                transform: Transform::from_translation(position.extend(0.0).xzy()),
+                ..default()
+            })
+            .insert(marker)
+            .insert(NotShadowCaster)
+            .insert(Name::new(format!("{:?}_Marker", marker)))
+            .insert(StateScoped(GameState::InGame));
+    }
+
+    // On mazing levels targets make their own way, the flow field overlay shows it instead
+    let mazing = level.build_grid.as_ref().map_or(false, |grid| grid.mazing);
+    if mazing || spline.length() <= 0.0 {
+        return;
+    }
+
+    commands
+        .spawn(PbrBundle {
+            mesh: meshes.add(road_mesh(&spline, ROAD_WIDTH)),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgb(0.55, 0.4, 0.25),
+                perceptual_roughness: 1.0,
+                ..default()
+            }),
+            ..default()
+        })
+        .insert(PathRoad)
+        .insert(NotShadowCaster)
+        .insert(Name::new("Road"))
+        .insert(StateScoped(GameState::InGame));
+
+    let arrow_mesh = meshes.add(flat_arrow_mesh());
+    let arrow_material = materials.add(StandardMaterial {
+        base_color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    let arrows = (spline.length() / ARROW_SPACING).ceil() as usize;
+    for arrow in 0..arrows {
+        commands
+            .spawn(PbrBundle {
+                mesh: arrow_mesh.clone(),
+                material: arrow_material.clone(),
+                ..default()
+            })
+            .insert(PathArrow {
+                offset: arrow as f32 * ARROW_SPACING,
+            })
+            .insert(NotShadowCaster)
+            .insert(StateScoped(GameState::InGame));
+    }
+
+    commands.insert_resource(PathView {
+        spline,
+        started: time.elapsed_seconds(),
+    });
+}
+
+fn move_path_arrows(
+    view: Option<Res<PathView>>,
+    time: Res<Time>,
+    mut arrows: Query<(&PathArrow, &mut Transform)>,
+) {
+    let Some(view) = view else {
+        return;
+    };
+
+    let seconds = time.elapsed_seconds() - view.started;
+    for (arrow, mut transform) in &mut arrows {
+        let distance = arrow_distance(arrow.offset, seconds, view.spline.length());
+        let position = view.spline.point_at(distance);
+        let tangent = view.spline.tangent_at(distance);
+        *transform = Transform::from_translation(position.extend(ROAD_HEIGHT).xzy())
+            .with_rotation(Quat::from_rotation_y(tangent.x.atan2(tangent.y)));
+    }
+}
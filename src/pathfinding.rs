@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+
+use bevy::{pbr::NotShadowCaster, prelude::*, utils::HashSet};
+
+use crate::build_grid::{occupied_cells, BuildGrid, GridCell};
+use crate::components::GameState;
+use crate::controls::{Controls, GameAction};
+use crate::level::Level;
+use crate::path_view::flat_arrow_mesh;
+use crate::state_scoped::StateScoped;
+
+const NEIGHBOURS: [IVec2; 4] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+];
+
+/// Steps to the exit from every cell of a mazing level's build grid, so any number of targets
+/// can find their way by looking at the cell they're in. Empty on other levels.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct FlowField {
+    grid: Option<BuildGrid>,
+    floor_size: f32,
+    cells_per_side: i32,
+    spawn: Vec2,
+    /// Indexed by `cell.y * cells_per_side + cell.x`, `None` for cells with no way out
+    distances: Vec<Option<u32>>,
+}
+
+impl FlowField {
+    /// Floods the grid outwards from the exit, around the `occupied` cells
+    pub fn compute(level: &Level, occupied: &HashSet<IVec2>) -> Self {
+        let Some(grid) = level.build_grid.as_ref().filter(|grid| grid.mazing) else {
+            return Self::default();
+        };
+        let cells_per_side = grid.cells_per_side(level.floor_size);
+        let mut field = Self {
+            grid: Some(grid.clone()),
+            floor_size: level.floor_size,
+            cells_per_side,
+            spawn: level.spawn,
+            distances: vec![None; (cells_per_side * cells_per_side).max(0) as usize],
+        };
+        let Some(goal) = grid.cell_at(level.exit(), level.floor_size) else {
+            return field;
+        };
+
+        // Every step costs the same, so a breadth first search finds the shortest distances
+        let mut queue = VecDeque::from([goal]);
+        let index = field.index(goal);
+        field.distances[index] = Some(0);
+        while let Some(cell) = queue.pop_front() {
+            let steps = field.distance(cell).unwrap() + 1;
+            for neighbour in NEIGHBOURS.map(|offset| cell + offset) {
+                if !field.on_grid(neighbour)
+                    || occupied.contains(&neighbour)
+                    || field.distance(neighbour).is_some()
+                {
+                    continue;
+                }
+                let index = field.index(neighbour);
+                field.distances[index] = Some(steps);
+                queue.push_back(neighbour);
+            }
+        }
+
+        field
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.grid.is_some()
+    }
+
+    fn on_grid(&self, cell: IVec2) -> bool {
+        cell.cmpge(IVec2::ZERO).all() && cell.cmplt(IVec2::splat(self.cells_per_side)).all()
+    }
+
+    fn index(&self, cell: IVec2) -> usize {
+        (cell.y * self.cells_per_side + cell.x) as usize
+    }
+
+    fn cell_at(&self, position: Vec2) -> Option<IVec2> {
+        self.grid.as_ref()?.cell_at(position, self.floor_size)
+    }
+
+    /// Steps from `cell` to the exit, `None` off the grid or when it's walled in
+    pub fn distance(&self, cell: IVec2) -> Option<u32> {
+        if !self.on_grid(cell) {
+            return None;
+        }
+        self.distances[self.index(cell)]
+    }
+
+    /// The neighbour to step to from `cell`, `None` at the exit or with no way out.
+    /// Also leads out of occupied cells, like one a tower was just built over.
+    pub fn next_cell(&self, cell: IVec2) -> Option<IVec2> {
+        if self.distance(cell) == Some(0) {
+            return None;
+        }
+        NEIGHBOURS
+            .map(|offset| cell + offset)
+            .into_iter()
+            .filter_map(|neighbour| Some((self.distance(neighbour)?, neighbour)))
+            .min_by_key(|(steps, _)| *steps)
+            .filter(|(steps, _)| self.distance(cell).map_or(true, |own| *steps < own))
+            .map(|(_, neighbour)| neighbour)
+    }
+
+    /// Where a target at `position` should head next, the middle of the next cell.
+    /// `None` when the field doesn't apply there and the path leads instead.
+    pub fn steer(&self, position: Vec2) -> Option<Vec2> {
+        let grid = self.grid.as_ref()?;
+        let next = self.next_cell(self.cell_at(position)?)?;
+        Some(grid.cell_center(next, self.floor_size))
+    }
+
+    /// Ground distance left to the exit's cell, targets still lining up first walk to the spawn
+    pub fn distance_to_exit(&self, position: Vec2) -> Option<f32> {
+        let grid = self.grid.as_ref()?;
+        match self.cell_at(position) {
+            Some(cell) => {
+                let to_center = position.distance(grid.cell_center(cell, self.floor_size));
+                Some(self.distance(cell)? as f32 * grid.cell_size + to_center)
+            }
+            None => Some(position.distance(self.spawn) + self.distance_to_exit(self.spawn)?),
+        }
+    }
+}
+
+/// Draws the flow field as arrows on the floor, toggled with `GameAction::ToggleFlowField`
+#[derive(Resource, Default)]
+pub struct FlowFieldOverlay {
+    pub visible: bool,
+}
+
+#[derive(Component)]
+pub struct FlowFieldArrow;
+
+#[derive(Resource)]
+struct FlowFieldArrowAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for FlowFieldArrowAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(flat_arrow_mesh());
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Color::YELLOW,
+                unlit: true,
+                ..default()
+            });
+        Self { mesh, material }
+    }
+}
+
+pub struct PathfindingPlugin;
+
+impl Plugin for PathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>()
+            .init_resource::<FlowFieldOverlay>()
+            .init_resource::<FlowFieldArrowAssets>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(reset_flow_field))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(toggle_flow_field_overlay),
+            )
+            // After Update's commands are applied, so towers built or sold this frame are seen
+            .add_system_to_stage(CoreStage::PostUpdate, update_flow_field)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                draw_flow_field.after(update_flow_field),
+            );
+    }
+}
+
+fn reset_flow_field(mut flow_field: ResMut<FlowField>, level: Res<Level>) {
+    *flow_field = FlowField::compute(&level, &occupied_cells(&level, []));
+}
+
+/// Computed again whenever a tower is built or sold, once for every target
+fn update_flow_field(
+    added: Query<(), Added<GridCell>>,
+    removed: RemovedComponents<GridCell>,
+    cells: Query<&GridCell>,
+    level: Res<Level>,
+    mut flow_field: ResMut<FlowField>,
+) {
+    if added.is_empty() && removed.iter().next().is_none() {
+        return;
+    }
+
+    let occupied = occupied_cells(&level, &cells);
+    *flow_field = FlowField::compute(&level, &occupied);
+}
+
+fn toggle_flow_field_overlay(controls: Controls, mut overlay: ResMut<FlowFieldOverlay>) {
+    if controls.just_pressed(GameAction::ToggleFlowField) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+fn draw_flow_field(
+    mut commands: Commands,
+    overlay: Res<FlowFieldOverlay>,
+    flow_field: Res<FlowField>,
+    assets: Res<FlowFieldArrowAssets>,
+    arrows: Query<Entity, With<FlowFieldArrow>>,
+) {
+    if !overlay.is_changed() && !flow_field.is_changed() {
+        return;
+    }
+
+    for arrow in &arrows {
+        commands.entity(arrow).despawn();
+    }
+
+    let Some(grid) = flow_field.grid.as_ref().filter(|_| overlay.visible) else {
+        return;
+    };
+
+    for y in 0..flow_field.cells_per_side {
+        for x in 0..flow_field.cells_per_side {
+            let cell = IVec2::new(x, y);
+            let Some(next) = flow_field.next_cell(cell) else {
+                continue;
+            };
+            let direction = (next - cell).as_vec2();
+            let center = grid.cell_center(cell, flow_field.floor_size);
+
+            commands
+                .spawn(PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: assets.material.clone(),
+                    transform: Transform::from_translation(center.extend(0.0).xzy())
+                        .with_rotation(Quat::from_rotation_y(direction.x.atan2(direction.y)))
+                        .with_scale(Vec3::splat(grid.cell_size)),
+                    ..default()
+                })
+                .insert(FlowFieldArrow)
+                .insert(NotShadowCaster)
+                // Left behind when the match ends, like everything else in it
+                .insert(StateScoped(GameState::InGame));
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::RapierConfiguration;
+
+use crate::controls::{Controls, GameAction};
+use crate::localization::LocalizedText;
+use crate::main_menu::spawn_button;
+use crate::settings::SettingsButton;
+use crate::state_scoped::StateScoped;
+use crate::GameState;
+
+#[derive(Component)]
+pub struct PauseMenuUIRoot;
+
+#[derive(Component)]
+pub struct ResumeButton;
+
+#[derive(Component)]
+pub struct RestartButton;
+
+#[derive(Component)]
+pub struct QuitToMenuButton;
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_pause)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Paused)
+                    .with_system(spawn_pause_menu)
+                    .with_system(stop_physics),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(start_physics))
+            .add_system_set(
+                SystemSet::on_update(GameState::Paused)
+                    .with_system(resume_button_clicked)
+                    .with_system(restart_button_clicked)
+                    .with_system(quit_to_menu_button_clicked),
+            );
+    }
+}
+
+fn toggle_pause(controls: Controls, mut game_state: ResMut<State<GameState>>) {
+    if !controls.just_pressed(GameAction::Pause) {
+        return;
+    }
+
+    // Errors only mean another transition is already queued this frame
+    let _ = match game_state.current() {
+        GameState::InGame => game_state.push(GameState::Paused),
+        GameState::Paused => game_state.pop(),
+        _ => Ok(()),
+    };
+}
+
+// Kinematic bodies don't move on their own, but collisions would still be reported
+fn stop_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+}
+
+fn start_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
+
+fn resume_button_clicked(
+    interactions: Query<&Interaction, (With<ResumeButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            game_state.pop().unwrap();
+        }
+    }
+}
+
+fn restart_button_clicked(
+    interactions: Query<&Interaction, (With<RestartButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            // Leaves both Paused and InGame, so the match is torn down and spawned again
+            game_state.replace(GameState::InGame).unwrap();
+        }
+    }
+}
+
+fn quit_to_menu_button_clicked(
+    interactions: Query<&Interaction, (With<QuitToMenuButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            game_state.replace(GameState::MainMenu).unwrap();
+        }
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let resume_button = spawn_button(&mut commands, &asset_server, "pause-resume", Color::GREEN);
+    commands.entity(resume_button).insert(ResumeButton);
+
+    let restart_button = spawn_button(
+        &mut commands,
+        &asset_server,
+        "button-restart",
+        Color::ORANGE,
+    );
+    commands.entity(restart_button).insert(RestartButton);
+
+    let settings_button =
+        spawn_button(&mut commands, &asset_server, "button-settings", Color::GRAY);
+    commands.entity(settings_button).insert(SettingsButton);
+
+    let quit_button = spawn_button(&mut commands, &asset_server, "pause-quit", Color::BLUE);
+    commands.entity(quit_button).insert(QuitToMenuButton);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        })
+        .insert(PauseMenuUIRoot)
+        .insert(StateScoped(GameState::Paused))
+        .with_children(|commands| {
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Percent(3.0)),
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size: 96.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(LocalizedText::new("pause-title"));
+        })
+        .add_child(resume_button)
+        .add_child(restart_button)
+        .add_child(settings_button)
+        .add_child(quit_button);
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+use crate::components::{GameState, TargetHitEvent};
 use crate::{Bullet, Health, Target};
 
 #[derive(Bundle)]
@@ -31,14 +32,23 @@ impl PhysicsBundle {
 
 fn bullet_collision_detection(
     mut commands: Commands,
-    bullet_query: Query<Entity, With<Bullet>>,
-    mut colliding_entities_query: Query<(&mut Health, &CollidingEntities), With<Target>>,
+    bullet_query: Query<(Entity, &Bullet)>,
+    mut colliding_entities_query: Query<(Entity, &mut Target, &mut Health, &CollidingEntities)>,
+    mut hit_events: EventWriter<TargetHitEvent>,
 ) {
-    for (mut health, colliding_entities) in colliding_entities_query.iter_mut() {
-        for bullet_entity in bullet_query.iter() {
+    for (target_entity, mut target, mut health, colliding_entities) in
+        colliding_entities_query.iter_mut()
+    {
+        for (bullet_entity, bullet) in bullet_query.iter() {
             if colliding_entities.contains(bullet_entity) {
                 commands.entity(bullet_entity).despawn_recursive();
-                health.value -= 1;
+                health.value -= bullet.damage;
+                target.last_hit_by = bullet.source;
+                hit_events.send(TargetHitEvent {
+                    target: target_entity,
+                    tower: bullet.source,
+                    damage: bullet.damage,
+                });
             }
         }
     }
@@ -48,6 +58,8 @@ pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system(bullet_collision_detection);
+        app.add_event::<TargetHitEvent>().add_system_set(
+            SystemSet::on_update(GameState::InGame).with_system(bullet_collision_detection),
+        );
     }
 }
@@ -1,5 +1,8 @@
 use bevy::prelude::*;
 
+use crate::difficulty::Difficulty;
+use crate::localization::LocalizedText;
+use crate::state_scoped::StateScoped;
 use crate::{GameState, TargetDeathEvent};
 
 // Could be a resource
@@ -37,45 +40,48 @@ impl Plugin for PlayerPlugin {
     }
 }
 
-fn spawn_player(mut commands: Commands) {
+fn spawn_player(mut commands: Commands, difficulty: Res<Difficulty>) {
     commands.spawn((
         Player {
-            money: 100,
-            health: 10,
+            money: difficulty.starting_money(),
+            health: difficulty.starting_lives(),
         },
         Name::new("Player"),
+        StateScoped(GameState::InGame),
     ));
 }
 
 fn give_money_on_kill(
     mut player: Query<&mut Player>,
     mut death_events: EventReader<TargetDeathEvent>,
+    difficulty: Res<Difficulty>,
 ) {
     let mut player = player.single_mut();
     for _event in death_events.iter() {
-        player.money += 10;
+        player.money += difficulty.bounty();
     }
 }
 
 fn update_player_ui(
     player: Query<&Player>,
     // The without here prevents queries from potentially matching the same text component (if one text entity had both ui comps)
-    mut money_ui: Query<&mut Text, (With<MoneyUI>, Without<HealthUI>)>,
-    mut health_ui: Query<&mut Text, With<HealthUI>>,
+    mut money_ui: Query<&mut LocalizedText, (With<MoneyUI>, Without<HealthUI>)>,
+    mut health_ui: Query<&mut LocalizedText, With<HealthUI>>,
 ) {
     //Won't panic: There must be 1 and only 1 of each of these entities
     let player = player.single();
     let mut money = money_ui.single_mut();
     let mut health = health_ui.single_mut();
 
-    *money = Text::from_section(
-        format!("Money: {}", player.money),
-        money.sections[0].style.clone(),
-    );
-    *health = Text::from_section(
-        format!("Health: {}", player.health),
-        health.sections[0].style.clone(),
-    );
+    // Only written when the numbers change, so the text isn't laid out again every frame
+    let new_money = LocalizedText::new("hud-money").with_arg("money", player.money);
+    if *money != new_money {
+        *money = new_money;
+    }
+    let new_health = LocalizedText::new("hud-health").with_arg("health", player.health);
+    if *health != new_health {
+        *health = new_health;
+    }
 }
 
 fn spawn_gameplay_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
@@ -91,6 +97,7 @@ fn spawn_gameplay_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
             ..default()
         })
         .insert(GamePlayUIRoot)
+        .insert(StateScoped(GameState::InGame))
         .with_children(|commands| {
             commands
                 .spawn(NodeBundle {
@@ -112,7 +119,7 @@ fn spawn_gameplay_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 ..default()
                             },
                             text: Text::from_section(
-                                "Player Money: XX",
+                                "",
                                 TextStyle {
                                     font: asset_server.load("FiraSans-Bold.ttf"),
                                     font_size: 36.0,
@@ -121,7 +128,8 @@ fn spawn_gameplay_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ),
                             ..default()
                         })
-                        .insert(MoneyUI);
+                        .insert(MoneyUI)
+                        .insert(LocalizedText::new("hud-money"));
                     commands
                         .spawn(TextBundle {
                             style: Style {
@@ -129,7 +137,7 @@ fn spawn_gameplay_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 ..default()
                             },
                             text: Text::from_section(
-                                "Player Health: XX",
+                                "",
                                 TextStyle {
                                     font: asset_server.load("FiraSans-Bold.ttf"),
                                     font_size: 36.0,
@@ -138,7 +146,8 @@ fn spawn_gameplay_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ),
                             ..default()
                         })
-                        .insert(HealthUI);
+                        .insert(HealthUI)
+                        .insert(LocalizedText::new("hud-health"));
                 });
         });
 }
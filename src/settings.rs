@@ -0,0 +1,468 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, window::WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::components::GameState;
+use crate::controls::{ControlsButton, GameAction, KeyBindings};
+use crate::localization::{Language, Localization, LocalizedText};
+use crate::main_menu::spawn_button;
+use crate::state_scoped::StateScoped;
+use crate::{HEIGHT, WIDTH};
+
+/// Where the game binary keeps its settings, relative to the working directory
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+/// Window sizes offered in the settings screen
+pub const RESOLUTIONS: [Vec2; 4] = [
+    Vec2::new(WIDTH, HEIGHT),
+    Vec2::new(1600.0, 900.0),
+    Vec2::new(1920.0, 1080.0),
+    Vec2::new(2560.0, 1440.0),
+];
+
+const VOLUME_STEP: f32 = 0.1;
+const UI_SCALE_STEP: f64 = 0.25;
+const MIN_UI_SCALE: f64 = 0.5;
+const MAX_UI_SCALE: f64 = 2.0;
+
+/// Player preferences, saved when leaving the settings screen and loaded before the window opens
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub ui_volume: f32,
+    pub window_mode: WindowSetting,
+    pub resolution: Vec2,
+    pub ui_scale: f64,
+    pub language: Language,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+            ui_volume: 1.0,
+            window_mode: WindowSetting::Windowed,
+            resolution: Vec2::new(WIDTH, HEIGHT),
+            ui_scale: 1.0,
+            language: Language::English,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowSetting {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowSetting {
+    pub const ALL: [WindowSetting; 3] = [
+        WindowSetting::Windowed,
+        WindowSetting::Borderless,
+        WindowSetting::Fullscreen,
+    ];
+
+    pub fn mode(&self) -> WindowMode {
+        match self {
+            WindowSetting::Windowed => WindowMode::Windowed,
+            WindowSetting::Borderless => WindowMode::BorderlessFullscreen,
+            WindowSetting::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            WindowSetting::Windowed => "window-windowed",
+            WindowSetting::Borderless => "window-borderless",
+            WindowSetting::Fullscreen => "window-fullscreen",
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, falling back to the defaults when it is missing or broken
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("ignoring invalid settings in {}: {}", path.display(), err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())?;
+        fs::write(path, contents)
+            .map_err(|err| format!("could not write {}: {}", path.display(), err))
+    }
+
+    /// Volume sound effects are played at
+    pub fn sfx_volume(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn ui_volume(&self) -> f32 {
+        self.master_volume * self.ui_volume
+    }
+
+    /// The primary window as the player last left it
+    pub fn window_descriptor(&self) -> WindowDescriptor {
+        WindowDescriptor {
+            width: self.resolution.x,
+            height: self.resolution.y,
+            mode: self.window_mode.mode(),
+            resizable: false,
+            ..default()
+        }
+    }
+
+    fn adjust(&mut self, option: SettingOption, step: i32) {
+        let volume = |volume: f32| (volume + VOLUME_STEP * step as f32).clamp(0.0, 1.0);
+        match option {
+            SettingOption::MasterVolume => self.master_volume = volume(self.master_volume),
+            SettingOption::SfxVolume => self.sfx_volume = volume(self.sfx_volume),
+            SettingOption::MusicVolume => self.music_volume = volume(self.music_volume),
+            SettingOption::UiVolume => self.ui_volume = volume(self.ui_volume),
+            SettingOption::WindowMode => {
+                self.window_mode = cycle(&WindowSetting::ALL, &self.window_mode, step)
+            }
+            SettingOption::Resolution => {
+                self.resolution = cycle(&RESOLUTIONS, &self.resolution, step)
+            }
+            SettingOption::UiScale => {
+                self.ui_scale =
+                    (self.ui_scale + UI_SCALE_STEP * step as f64).clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+            }
+            SettingOption::Language => self.language = cycle(&Language::ALL, &self.language, step),
+        }
+    }
+
+    fn value_text(&self, option: SettingOption, localization: &Localization) -> String {
+        let percent = |volume: f32| format!("{:.0}%", volume * 100.0);
+        match option {
+            SettingOption::MasterVolume => percent(self.master_volume),
+            SettingOption::SfxVolume => percent(self.sfx_volume),
+            SettingOption::MusicVolume => percent(self.music_volume),
+            SettingOption::UiVolume => percent(self.ui_volume),
+            SettingOption::WindowMode => localization.get(self.window_mode.text_key()),
+            SettingOption::Resolution => {
+                format!("{}x{}", self.resolution.x, self.resolution.y)
+            }
+            SettingOption::UiScale => format!("{:.2}x", self.ui_scale),
+            SettingOption::Language => self.language.native_name().to_string(),
+        }
+    }
+}
+
+/// The next or previous entry of `options`, values not in the list start over from the first
+fn cycle<T: Copy + PartialEq>(options: &[T], current: &T, step: i32) -> T {
+    let index = options
+        .iter()
+        .position(|option| option == current)
+        .map(|index| (index as i32 + step).rem_euclid(options.len() as i32) as usize)
+        .unwrap_or(0);
+    options[index]
+}
+
+/// Set by the game binary, settings aren't saved without it
+#[derive(Resource)]
+pub struct SettingsPath(pub PathBuf);
+
+/// One row of the settings screen
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingOption {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    UiVolume,
+    WindowMode,
+    Resolution,
+    UiScale,
+    Language,
+}
+
+impl SettingOption {
+    pub const ALL: [SettingOption; 8] = [
+        SettingOption::MasterVolume,
+        SettingOption::SfxVolume,
+        SettingOption::MusicVolume,
+        SettingOption::UiVolume,
+        SettingOption::WindowMode,
+        SettingOption::Resolution,
+        SettingOption::UiScale,
+        SettingOption::Language,
+    ];
+
+    fn text_key(&self) -> &'static str {
+        match self {
+            SettingOption::MasterVolume => "settings-master-volume",
+            SettingOption::SfxVolume => "settings-sfx-volume",
+            SettingOption::MusicVolume => "settings-music-volume",
+            SettingOption::UiVolume => "settings-ui-volume",
+            SettingOption::WindowMode => "settings-window-mode",
+            SettingOption::Resolution => "settings-resolution",
+            SettingOption::UiScale => "settings-ui-scale",
+            SettingOption::Language => "settings-language",
+        }
+    }
+}
+
+/// Changes its row's setting by `step` when clicked
+#[derive(Component)]
+pub struct SettingStepButton {
+    pub option: SettingOption,
+    pub step: i32,
+}
+
+#[derive(Component)]
+pub struct SettingValueText(pub SettingOption);
+
+#[derive(Component)]
+pub struct SettingsUIRoot;
+
+#[derive(Component)]
+pub struct SettingsBackButton;
+
+/// Opens the settings screen from the main menu or the pause menu
+#[derive(Component)]
+pub struct SettingsButton;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .add_system(apply_settings)
+            .add_system(settings_button_clicked)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Settings).with_system(spawn_settings_menu),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Settings).with_system(save_settings))
+            .add_system_set(
+                SystemSet::on_update(GameState::Settings)
+                    .with_system(setting_step_button_clicked)
+                    .with_system(update_setting_values.after(setting_step_button_clicked))
+                    .with_system(back_button_clicked),
+            );
+    }
+}
+
+fn apply_settings(
+    settings: Res<Settings>,
+    windows: Option<ResMut<Windows>>,
+    ui_scale: Option<ResMut<UiScale>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Some(mut ui_scale) = ui_scale {
+        ui_scale.scale = settings.ui_scale;
+    }
+
+    if let Some(window) = windows
+        .map(|windows| windows.into_inner())
+        .and_then(|windows| windows.get_primary_mut())
+    {
+        window.set_mode(settings.window_mode.mode());
+        window.set_resolution(settings.resolution.x, settings.resolution.y);
+    }
+}
+
+fn save_settings(settings: Res<Settings>, path: Option<Res<SettingsPath>>) {
+    let Some(path) = path else {
+        return;
+    };
+
+    if let Err(err) = settings.save(&path.0) {
+        warn!("{}", err);
+    }
+}
+
+// Both menus have this button, it only exists while one of them is the current state
+fn settings_button_clicked(
+    interactions: Query<&Interaction, (With<SettingsButton>, Changed<Interaction>)>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    for interaction in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            let _ = game_state.push(GameState::Settings);
+        }
+    }
+}
+
+fn setting_step_button_clicked(
+    interactions: Query<(&Interaction, &SettingStepButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+) {
+    for (interaction, button) in &interactions {
+        if matches!(interaction, Interaction::Clicked) {
+            settings.adjust(button.option, button.step);
+        }
+    }
+}
+
+// Window mode names are translated, so this also follows language changes
+fn update_setting_values(
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+    mut values: Query<(&mut Text, &SettingValueText)>,
+) {
+    if !settings.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    for (mut text, value) in &mut values {
+        text.sections[0].value = settings.value_text(value.0, &localization);
+    }
+}
+
+fn back_button_clicked(
+    interactions: Query<&Interaction, (With<SettingsBackButton>, Changed<Interaction>)>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    let clicked = interactions
+        .iter()
+        .any(|interaction| matches!(interaction, Interaction::Clicked));
+
+    if clicked || bindings.just_pressed(GameAction::Pause, &keyboard) {
+        let _ = game_state.pop();
+    }
+}
+
+fn spawn_settings_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+) {
+    let controls_button =
+        spawn_button(&mut commands, &asset_server, "button-controls", Color::GRAY);
+    commands.entity(controls_button).insert(ControlsButton);
+
+    let back_button = spawn_button(&mut commands, &asset_server, "button-back", Color::BLUE);
+    commands.entity(back_button).insert(SettingsBackButton);
+
+    let text_style = |font_size: f32| TextStyle {
+        font: asset_server.load("FiraSans-Bold.ttf"),
+        font_size,
+        color: Color::BLACK,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            // Drawn over whichever menu opened it
+            background_color: Color::rgb(0.8, 0.8, 0.8).into(),
+            z_index: ZIndex::Global(20),
+            ..default()
+        })
+        .insert(SettingsUIRoot)
+        .insert(StateScoped(GameState::Settings))
+        .with_children(|commands| {
+            commands
+                .spawn(TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::all(Val::Percent(2.0)),
+                        ..default()
+                    },
+                    text: Text::from_section("", text_style(96.0)),
+                    ..default()
+                })
+                .insert(LocalizedText::new("settings-title"));
+
+            for option in SettingOption::ALL {
+                commands
+                    .spawn(NodeBundle {
+                        style: Style {
+                            align_self: AlignSelf::Center,
+                            align_items: AlignItems::Center,
+                            flex_direction: FlexDirection::Row,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|commands| {
+                        commands
+                            .spawn(TextBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(320.0), Val::Auto),
+                                    ..default()
+                                },
+                                text: Text::from_section("", text_style(36.0)),
+                                ..default()
+                            })
+                            .insert(LocalizedText::new(option.text_key()));
+
+                        spawn_step_button(commands, option, -1, text_style(36.0));
+                        commands
+                            .spawn(TextBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(200.0), Val::Auto),
+                                    ..default()
+                                },
+                                text: Text::from_section(
+                                    settings.value_text(option, &localization),
+                                    text_style(36.0),
+                                ),
+                                ..default()
+                            })
+                            .insert(SettingValueText(option));
+                        spawn_step_button(commands, option, 1, text_style(36.0));
+                    });
+            }
+        })
+        .push_children(&[controls_button, back_button]);
+}
+
+fn spawn_step_button(
+    commands: &mut ChildBuilder,
+    option: SettingOption,
+    step: i32,
+    text_style: TextStyle,
+) {
+    commands
+        .spawn(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(48.0), Val::Px(48.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            background_color: Color::GRAY.into(),
+            ..default()
+        })
+        .insert(SettingStepButton { option, step })
+        .with_children(|commands| {
+            let label = if step < 0 { "<" } else { ">" };
+            commands.spawn(TextBundle::from_section(label, text_style));
+        });
+}
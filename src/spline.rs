@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+/// Points sampled along each curve segment to measure its length
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+/// A smooth curve through a list of ground points, walked by distance instead of by point.
+///
+/// The curve is a Catmull-Rom spline, which passes through every point. Sampling it by arc
+/// length keeps the speed the same on long straights and tight turns alike.
+#[derive(Clone, Debug, Default)]
+pub struct PathSpline {
+    points: Vec<Vec2>,
+    /// Distance along the curve at each sample, samples are spread evenly over the curve parameter
+    lengths: Vec<f32>,
+}
+
+impl PathSpline {
+    pub fn new(points: impl IntoIterator<Item = Vec2>) -> Self {
+        let mut points: Vec<Vec2> = points.into_iter().collect();
+        // Repeated points would give segments with no length and no direction
+        points.dedup();
+
+        let mut spline = Self {
+            points,
+            lengths: vec![0.0],
+        };
+
+        let samples = spline.segments() * SAMPLES_PER_SEGMENT;
+        let mut previous = spline.evaluate(0.0);
+        let mut length = 0.0;
+        for sample in 1..=samples {
+            let point = spline.evaluate(sample as f32 / SAMPLES_PER_SEGMENT as f32);
+            length += previous.distance(point);
+            spline.lengths.push(length);
+            previous = point;
+        }
+
+        spline
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    /// Distance along the whole curve from the first point to the last
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or_default()
+    }
+
+    /// The point `distance` along the curve.
+    ///
+    /// Before the start and past the end it carries on in a straight line, which is where
+    /// targets line up before walking the path.
+    pub fn point_at(&self, distance: f32) -> Vec2 {
+        let Some(start) = self.points.first().copied() else {
+            return Vec2::ZERO;
+        };
+        if distance <= 0.0 {
+            return start + self.tangent_at(0.0) * distance;
+        }
+        if distance >= self.length() {
+            let end = self.points.last().copied().unwrap();
+            return end + self.tangent_at(self.length()) * (distance - self.length());
+        }
+        self.evaluate(self.parameter_at(distance))
+    }
+
+    /// Direction the curve goes in `distance` along it, of length one
+    pub fn tangent_at(&self, distance: f32) -> Vec2 {
+        if self.segments() == 0 {
+            return Vec2::X;
+        }
+        let parameter = self.parameter_at(distance.clamp(0.0, self.length()));
+        let (p0, p1, p2, p3, t) = self.segment_at(parameter);
+
+        let tangent = 0.5
+            * ((p2 - p0)
+                + 2.0 * (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t
+                + 3.0 * (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * t * t);
+        tangent.try_normalize().unwrap_or((p2 - p1).normalize())
+    }
+
+    /// Distance from `point` to the nearest part of the curve, measured against its samples
+    pub fn distance_to(&self, point: Vec2) -> f32 {
+        let samples: Vec<Vec2> = (0..self.lengths.len())
+            .map(|sample| self.evaluate(sample as f32 / SAMPLES_PER_SEGMENT as f32))
+            .collect();
+        if samples.len() == 1 {
+            return point.distance(samples[0]);
+        }
+
+        samples
+            .windows(2)
+            .map(|chord| {
+                let (start, end) = (chord[0], chord[1]);
+                let along = end - start;
+                let t = if along == Vec2::ZERO {
+                    0.0
+                } else {
+                    ((point - start).dot(along) / along.length_squared()).clamp(0.0, 1.0)
+                };
+                point.distance(start + along * t)
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    fn segments(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    /// Curve parameter at `distance` along it, whole numbers are the points
+    fn parameter_at(&self, distance: f32) -> f32 {
+        let after = self
+            .lengths
+            .partition_point(|length| *length < distance)
+            .clamp(1, self.lengths.len() - 1);
+        let (before_length, after_length) = (self.lengths[after - 1], self.lengths[after]);
+        let fraction = if after_length > before_length {
+            (distance - before_length) / (after_length - before_length)
+        } else {
+            0.0
+        };
+        (after - 1) as f32 / SAMPLES_PER_SEGMENT as f32 + fraction / SAMPLES_PER_SEGMENT as f32
+    }
+
+    /// The four control points around the segment `parameter` falls in, and how far along it is.
+    /// The ends are mirrored so the curve leaves the first point heading straight at the second.
+    fn segment_at(&self, parameter: f32) -> (Vec2, Vec2, Vec2, Vec2, f32) {
+        let last = self.points.len() - 1;
+        let segment = (parameter.floor() as usize).min(last - 1);
+        let point = |index: isize| {
+            if index < 0 {
+                2.0 * self.points[0] - self.points[1]
+            } else if index as usize > last {
+                2.0 * self.points[last] - self.points[last - 1]
+            } else {
+                self.points[index as usize]
+            }
+        };
+
+        let index = segment as isize;
+        (
+            point(index - 1),
+            point(index),
+            point(index + 1),
+            point(index + 2),
+            parameter - segment as f32,
+        )
+    }
+
+    fn evaluate(&self, parameter: f32) -> Vec2 {
+        match self.points.len() {
+            0 => Vec2::ZERO,
+            1 => self.points[0],
+            _ => {
+                let (p0, p1, p2, p3, t) = self.segment_at(parameter);
+                0.5 * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+                    + (-p0 + 3.0 * p1 - 3.0 * p2 + p3) * t * t * t)
+            }
+        }
+    }
+}
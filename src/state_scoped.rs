@@ -0,0 +1,43 @@
+use bevy::{ecs::schedule::StateData, prelude::*};
+
+/// Marks an entity as belonging to a state, it is despawned along with its children when that state is exited.
+///
+/// Only put it on root entities, children go with their parent.
+#[derive(Component, Clone, Debug)]
+pub struct StateScoped<S: StateData>(pub S);
+
+/// Despawns `StateScoped<S>` entities when leaving any of the given states
+pub struct StateScopedPlugin<S: StateData> {
+    states: Vec<S>,
+}
+
+impl<S: StateData> StateScopedPlugin<S> {
+    pub fn new(states: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            states: states.into_iter().collect(),
+        }
+    }
+}
+
+impl<S: StateData> Plugin for StateScopedPlugin<S> {
+    fn build(&self, app: &mut App) {
+        for state in &self.states {
+            app.add_system_set(
+                SystemSet::on_exit(state.clone()).with_system(despawn_state_scoped::<S>),
+            );
+        }
+    }
+}
+
+// While exiting, the current state is still the one being left
+fn despawn_state_scoped<S: StateData>(
+    mut commands: Commands,
+    state: Res<State<S>>,
+    entities: Query<(Entity, &StateScoped<S>)>,
+) {
+    for (entity, scope) in &entities {
+        if scope.0 == *state.current() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::components::{
+    GameState, TargetDeathEvent, TargetHitEvent, TargetLeakedEvent, TowerFiredEvent, TowerType,
+};
+use crate::difficulty::Difficulty;
+use crate::game_speed::GameTime;
+use crate::player::Player;
+
+/// Clearing a level faster than this many seconds earns a time bonus
+pub const PAR_TIME_SECONDS: f32 = 300.0;
+/// Score for every second under the par time
+pub const TIME_BONUS_PER_SECOND: u32 = 2;
+
+/// Running totals for the current match, reset whenever a match starts
+#[derive(Resource, Default, Clone, Debug)]
+pub struct MatchStats {
+    pub towers: HashMap<TowerType, CombatStats>,
+    pub kills: u32,
+    pub lives_lost: u32,
+    pub money_earned: u32,
+    /// Game time spent in the match, not counting pauses
+    pub duration: Duration,
+    /// Health and money the player had when the match ended
+    pub lives_left: u32,
+    pub money_left: u32,
+}
+
+/// What a tower, or every tower of a type, did in the match.
+/// On tower entities it counts that tower alone.
+#[derive(Component, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CombatStats {
+    pub shots: u32,
+    pub hits: u32,
+    pub damage: u32,
+    pub kills: u32,
+}
+
+impl CombatStats {
+    /// Fraction of shots that hit something, zero when nothing was fired
+    pub fn hit_rate(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots as f32
+        }
+    }
+}
+
+impl MatchStats {
+    pub fn tower_type(&self, tower_type: TowerType) -> CombatStats {
+        self.towers.get(&tower_type).copied().unwrap_or_default()
+    }
+
+    /// Points for kills, lives kept and unspent money, with a bonus for clearing the level quickly
+    pub fn score(&self) -> u32 {
+        // A game over always ends with no lives left, finishing fast only counts for a win
+        let time_bonus = if self.lives_left > 0 {
+            (PAR_TIME_SECONDS - self.duration.as_secs_f32()).max(0.0) as u32 * TIME_BONUS_PER_SECOND
+        } else {
+            0
+        };
+        self.kills * 10 + self.lives_left * 100 + self.money_left + time_bonus
+    }
+
+    /// One to three stars depending on how many lives were kept
+    pub fn stars(&self) -> u32 {
+        let starting_lives = self.lives_left + self.lives_lost;
+        if self.lives_lost == 0 {
+            3
+        } else if self.lives_left * 2 >= starting_lives {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Totals over every tower type
+    pub fn total(&self) -> CombatStats {
+        self.towers
+            .values()
+            .fold(CombatStats::default(), |total, stats| CombatStats {
+                shots: total.shots + stats.shots,
+                hits: total.hits + stats.hits,
+                damage: total.damage + stats.damage,
+                kills: total.kills + stats.kills,
+            })
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchStats>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(reset_stats))
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(record_duration))
+            .add_system_set(SystemSet::on_exit(GameState::InGame).with_system(record_final_player))
+            // Events are read after Update so the ones sent on the frame a match ends still count
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_system(record_shots)
+                    .with_system(record_hits)
+                    .with_system(record_kills)
+                    .with_system(record_leaks),
+            );
+    }
+}
+
+fn reset_stats(mut stats: ResMut<MatchStats>) {
+    *stats = MatchStats::default();
+}
+
+fn record_shots(
+    mut stats: ResMut<MatchStats>,
+    mut fired_events: EventReader<TowerFiredEvent>,
+    mut towers: Query<&mut CombatStats>,
+) {
+    for event in fired_events.iter() {
+        stats.towers.entry(event.tower_type).or_default().shots += 1;
+        if let Ok(mut tower_stats) = towers.get_mut(event.tower) {
+            tower_stats.shots += 1;
+        }
+    }
+}
+
+fn record_hits(
+    mut stats: ResMut<MatchStats>,
+    mut hit_events: EventReader<TargetHitEvent>,
+    mut towers: Query<(&TowerType, &mut CombatStats)>,
+) {
+    for event in hit_events.iter() {
+        // Towers sold while their bullets were still flying don't count
+        let Some((tower_type, mut tower_stats)) =
+            event.tower.and_then(|tower| towers.get_mut(tower).ok())
+        else {
+            continue;
+        };
+
+        let damage = event.damage.max(0) as u32;
+        let type_stats = stats.towers.entry(*tower_type).or_default();
+        type_stats.hits += 1;
+        type_stats.damage += damage;
+        tower_stats.hits += 1;
+        tower_stats.damage += damage;
+    }
+}
+
+fn record_kills(
+    mut stats: ResMut<MatchStats>,
+    mut death_events: EventReader<TargetDeathEvent>,
+    mut towers: Query<(&TowerType, &mut CombatStats)>,
+    difficulty: Res<Difficulty>,
+) {
+    for event in death_events.iter() {
+        stats.kills += 1;
+        stats.money_earned += difficulty.bounty();
+
+        if let Some((tower_type, mut tower_stats)) =
+            event.killed_by.and_then(|tower| towers.get_mut(tower).ok())
+        {
+            stats.towers.entry(*tower_type).or_default().kills += 1;
+            tower_stats.kills += 1;
+        }
+    }
+}
+
+fn record_leaks(mut stats: ResMut<MatchStats>, mut leak_events: EventReader<TargetLeakedEvent>) {
+    for _event in leak_events.iter() {
+        stats.lives_lost += 1;
+    }
+}
+
+fn record_duration(mut stats: ResMut<MatchStats>, time: GameTime) {
+    stats.duration += time.delta();
+}
+
+// The player is despawned with the rest of the match, so the end screens read these copies
+fn record_final_player(mut stats: ResMut<MatchStats>, player: Query<&Player>) {
+    if let Ok(player) = player.get_single() {
+        stats.lives_left = player.health;
+        stats.money_left = player.money;
+    }
+}
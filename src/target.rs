@@ -1,8 +1,16 @@
 use bevy::{math::Vec3Swizzles, prelude::*};
 
-use crate::components::GameState;
-pub use crate::components::{Health, Target, TargetDeathEvent, TargetPath, Tower};
+use crate::components::{GameAssets, GameState};
+pub use crate::components::{
+    Health, Target, TargetDeathEvent, TargetLeakedEvent, TargetPath, Tower,
+};
+use crate::game_speed::GameTime;
+use crate::level::Level;
+use crate::pathfinding::FlowField;
+use crate::physics::PhysicsBundle;
 pub use crate::player::Player;
+use crate::spline::PathSpline;
+use crate::state_scoped::StateScoped;
 
 #[derive(Default)]
 pub struct TargetPlugin;
@@ -13,13 +21,9 @@ impl Plugin for TargetPlugin {
             .register_type::<Target>()
             .register_type::<Health>()
             .add_event::<TargetDeathEvent>()
-            .insert_resource(TargetPath {
-                waypoints: vec![
-                    Vec2::new(6.0, 2.0),
-                    Vec2::new(6.0, 6.0),
-                    Vec2::new(9.0, 9.0),
-                ],
-            })
+            .add_event::<TargetLeakedEvent>()
+            .init_resource::<TargetPath>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(load_target_path))
             .add_system_set(
                 SystemSet::on_update(GameState::InGame)
                     .with_system(move_targets)
@@ -29,37 +33,85 @@ impl Plugin for TargetPlugin {
     }
 }
 
+fn load_target_path(mut path: ResMut<TargetPath>, level: Res<Level>) {
+    path.spline = PathSpline::new(level.path());
+}
+
+pub fn spawn_target(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    position: Vec3,
+    distance: f32,
+    health: i32,
+    speed: f32,
+) -> Entity {
+    commands
+        .spawn(SceneBundle {
+            scene: assets.target_scene.clone(),
+            transform: Transform::from_translation(position),
+            ..Default::default()
+        })
+        .insert(Target {
+            speed,
+            distance,
+            ..Default::default()
+        })
+        .insert(Health { value: health })
+        .insert(Name::new("Target"))
+        .insert(StateScoped(GameState::InGame))
+        .insert(PhysicsBundle::moving_entity(Vec3::new(0.2, 0.2, 0.2)))
+        .id()
+}
+
 fn move_targets(
     mut targets: Query<(&mut Target, &mut Transform)>,
     path: Res<TargetPath>,
-    time: Res<Time>,
+    flow_field: Res<FlowField>,
+    time: GameTime,
 ) {
     for (mut target, mut transform) in &mut targets {
         let delta = target.speed * time.delta_seconds();
-        let delta_target = path.waypoints[target.path_index] - transform.translation.xz();
+        //Copy for ownership reasons
+        let y = transform.translation.y;
+
+        // On a mazing grid the flow field leads the way once past the spawn, up to the exit
+        if flow_field.is_active() && target.distance >= 0.0 {
+            let exit = path.spline.point_at(path.spline.length());
+            let heading = flow_field.steer(transform.translation.xz()).unwrap_or(exit);
+            let delta_target = heading - transform.translation.xz();
 
-        // This step will get us closer to the goal
-        if delta_target.length() > delta {
-            let movement = delta_target.normalize() * delta;
-            transform.translation += movement.extend(0.0).xzy();
-            //Copy for ownership reasons
-            let y = transform.translation.y;
-            transform.look_at(path.waypoints[target.path_index].extend(y).xzy(), Vec3::Y);
-        } else {
-            // At current step
-            target.path_index += 1;
+            // This step will get us closer to the goal
+            if delta_target.length() > delta {
+                let movement = delta_target.normalize() * delta;
+                transform.translation += movement.extend(0.0).xzy();
+                transform.look_at(heading.extend(y).xzy(), Vec3::Y);
+            } else if heading == exit {
+                target.distance = path.spline.length();
+            } else {
+                // In the middle of a cell, the field points on to the next one from here
+                transform.translation = heading.extend(y).xzy();
+            }
+            continue;
         }
+
+        target.distance += delta;
+        let position = path.spline.point_at(target.distance);
+        let tangent = path.spline.tangent_at(target.distance);
+        transform.translation = position.extend(y).xzy();
+        transform.look_at((position + tangent).extend(y).xzy(), Vec3::Y);
     }
 }
 
 fn target_death(
     mut commands: Commands,
-    targets: Query<(Entity, &Health)>,
+    targets: Query<(Entity, &Health, &Target)>,
     mut death_event_writer: EventWriter<TargetDeathEvent>,
 ) {
-    for (entity, health) in &targets {
+    for (entity, health, target) in &targets {
         if health.value <= 0 {
-            death_event_writer.send(TargetDeathEvent);
+            death_event_writer.send(TargetDeathEvent {
+                killed_by: target.last_hit_by,
+            });
             commands.entity(entity).despawn_recursive();
         }
     }
@@ -70,25 +122,24 @@ fn hurt_player(
     targets: Query<(Entity, &Target)>,
     path: Res<TargetPath>,
     mut player: Query<&mut Player>,
-    audio: Res<Audio>,
-    asset_server: Res<AssetServer>,
+    mut leak_events: EventWriter<TargetLeakedEvent>,
     mut game_state: ResMut<State<GameState>>,
 ) {
     for (entity, target) in &targets {
-        if target.path_index >= path.waypoints.len() {
+        if target.distance >= path.spline.length() {
             commands.entity(entity).despawn_recursive();
-
-            //Enemies reaching the end of their path could write an event to cause the player to take damage or play audio
-            audio.play(asset_server.load("damage.wav"));
+            // The audio manager plays the leak sound
+            leak_events.send(TargetLeakedEvent);
 
             let mut player = player.single_mut();
             if player.health > 0 {
                 player.health -= 1;
-            }
 
-            if player.health == 0 {
-                info!("GAME OVER");
-                game_state.set(GameState::GameOver).unwrap();
+                // Only the leak that takes the last life ends the game, several can land in one frame
+                if player.health == 0 {
+                    info!("GAME OVER");
+                    game_state.set(GameState::GameOver).unwrap();
+                }
             }
         }
     }
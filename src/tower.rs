@@ -1,33 +1,61 @@
 use bevy::prelude::*;
-use bevy::utils::FloatOrd;
+use bevy::{
+    ecs::{query::QuerySingleError, system::SystemParam},
+    math::Vec3Swizzles,
+    pbr::NotShadowCaster,
+    utils::FloatOrd,
+};
+use bevy_mod_picking::{Highlighting, PickableBundle, Selection};
 
-use crate::components::TowerButtonState;
+use crate::build_grid::{GridCell, Placement};
 pub use crate::components::{Bullet, GameAssets, Health, Lifetime, Target, Tower, TowerType};
+use crate::components::{
+    NotEnoughMoneyEvent, TargetPath, TargetingMode, TowerBase, TowerButtonState, TowerFiredEvent,
+    TowerUIRoot, MAX_TOWER_LEVEL,
+};
+use crate::controls::{Controls, GameAction};
+use crate::game_speed::GameTime;
+use crate::pathfinding::FlowField;
 use crate::physics::PhysicsBundle;
 use crate::*;
 
+/// Height of the tower base capsules above the floor, towers are built at the same height
+pub const TOWER_BASE_HEIGHT: f32 = 0.8;
+
 fn tower_shooting(
     mut commands: Commands,
-    targets: Query<&GlobalTransform, With<Target>>,
+    targets: Query<(&GlobalTransform, &Target, &Health)>,
+    path: Res<TargetPath>,
+    flow_field: Res<FlowField>,
     bullet_assets: Res<GameAssets>,
-    mut towers: Query<(Entity, &mut Tower, &TowerType, &GlobalTransform)>,
-    time: Res<Time>,
+    mut towers: Query<(
+        Entity,
+        &mut Tower,
+        &TowerType,
+        &TargetingMode,
+        &GlobalTransform,
+    )>,
+    mut fired_events: EventWriter<TowerFiredEvent>,
+    time: GameTime,
 ) {
-    for (tower_ent, mut tower, tower_type, transform) in &mut towers {
+    for (tower_ent, mut tower, tower_type, targeting, transform) in &mut towers {
         tower.shooting_timer.tick(time.delta());
         {
             if tower.shooting_timer.just_finished() {
                 let bullet_spawn: Vec3 = transform.translation() + tower.bullet_offset;
 
-                let direction: Option<Vec3> = targets
-                    .iter()
-                    .min_by_key(|target_transform| {
-                        FloatOrd(Vec3::distance(target_transform.translation(), bullet_spawn))
-                    })
-                    .map(|closest_target| closest_target.translation() - bullet_spawn);
+                let direction: Option<Vec3> =
+                    pick_target(&targets, &path, &flow_field, *targeting, bullet_spawn)
+                        .map(|target| target - bullet_spawn);
 
                 if let Some(direction) = direction {
-                    let (model, bullet) = tower_type.get_bullet(direction, &bullet_assets);
+                    let (model, mut bullet) = tower_type.get_bullet(direction, &bullet_assets);
+                    bullet.source = Some(tower_ent);
+                    bullet.damage = tower.damage();
+                    fired_events.send(TowerFiredEvent {
+                        tower: tower_ent,
+                        tower_type: *tower_type,
+                    });
                     commands.entity(tower_ent).with_children(|commands| {
                         commands
                             .spawn(SceneBundle {
@@ -48,67 +76,322 @@ fn tower_shooting(
     }
 }
 
-// fn build_tower(
-//     mut commands: Commands,
-//     selection: Query<(Entity, &Selection, &Transform)>,
-//     keyboard: Res<Input<KeyCode>>,
+/// Position of the target a tower at `from` should shoot
+fn pick_target(
+    targets: &Query<(&GlobalTransform, &Target, &Health)>,
+    path: &TargetPath,
+    flow_field: &FlowField,
+    targeting: TargetingMode,
+    from: Vec3,
+) -> Option<Vec3> {
+    // Further along means less of the way left to walk, through the maze on mazing levels
+    let remaining = |transform: &GlobalTransform, target: &Target| {
+        FloatOrd(
+            flow_field
+                .distance_to_exit(transform.translation().xz())
+                .unwrap_or_else(|| target.distance_to_exit(&path.spline)),
+        )
+    };
+    let distance = |transform: &GlobalTransform| FloatOrd(transform.translation().distance(from));
+
+    let targets = targets.iter();
+    let picked = match targeting {
+        TargetingMode::First => {
+            targets.min_by_key(|(transform, target, _)| remaining(transform, target))
+        }
+        TargetingMode::Last => {
+            targets.max_by_key(|(transform, target, _)| remaining(transform, target))
+        }
+        TargetingMode::Closest => targets.min_by_key(|(transform, _, _)| distance(transform)),
+        TargetingMode::Strongest => targets.max_by_key(|(_, _, health)| health.value),
+    };
+
+    picked.map(|(transform, _, _)| transform.translation())
+}
 
-//     assets: Res<GameAssets>,
-// ) {
-//     if keyboard.just_pressed(KeyCode::Space) {
-//         for (entity, selection, transform) in &selection {
-//             if selection.selected() {
-//                 commands.entity(entity).despawn_recursive();
-//                 spawn_tower(&mut commands, &assets, transform.translation);
-//             }
-//         }
-//     }
-// }
+/// Everything needed to build on the selected tower bases or start placing on the build grid,
+/// shared by the build buttons and hotkeys
+#[derive(SystemParam)]
+pub struct TowerBuilder<'w, 's> {
+    commands: Commands<'w, 's>,
+    bases: Query<'w, 's, (Entity, &'static Selection, &'static Transform), With<TowerBase>>,
+    player: Query<'w, 's, &'static mut Player>,
+    assets: Res<'w, GameAssets>,
+    picking: Res<'w, TowerPickingAssets>,
+    money_events: EventWriter<'w, 's, NotEnoughMoneyEvent>,
+    level: Res<'w, Level>,
+    placement: ResMut<'w, Placement>,
+}
+
+impl TowerBuilder<'_, '_> {
+    /// Builds on the selected bases, or on levels with a build grid picks up a tower to place
+    pub fn build(&mut self, tower_type: TowerType) {
+        let any_selected = self
+            .bases
+            .iter()
+            .any(|(_, selection, _)| selection.selected());
+        if self.level.build_grid.is_some() && !any_selected {
+            self.placement.tower_type = Some(tower_type);
+        } else {
+            self.build_on_selected(tower_type);
+        }
+    }
+
+    /// Builds `tower_type` on every selected base the player can pay for
+    pub fn build_on_selected(&mut self, tower_type: TowerType) {
+        let mut player = self.player.single_mut();
+
+        for (entity, selection, transform) in &self.bases {
+            if !selection.selected() {
+                continue;
+            }
+
+            if player.money < tower_type.cost() {
+                self.money_events.send(NotEnoughMoneyEvent);
+                continue;
+            }
+
+            player.money -= tower_type.cost();
+            //Remove the base model/hitbox
+            self.commands.entity(entity).despawn_recursive();
+            spawn_tower(
+                &mut self.commands,
+                &self.assets,
+                &self.picking,
+                transform.translation,
+                tower_type,
+            );
+        }
+    }
+}
 
 fn tower_button_clicked(
-    interaction: Query<(&Interaction, &TowerType, &TowerButtonState), Changed<Interaction>>,
-    mut commands: Commands,
-    selection: Query<(Entity, &Selection, &Transform)>,
-    mut player: Query<&mut Player>,
-    assets: Res<GameAssets>,
+    interaction: Query<(&Interaction, &TowerType), (With<TowerButtonState>, Changed<Interaction>)>,
+    mut builder: TowerBuilder,
 ) {
-    let mut player = player.single_mut();
-
-    for (interaction, tower_type, button_state) in &interaction {
+    for (interaction, tower_type) in &interaction {
         if matches!(interaction, Interaction::Clicked) {
-            for (entity, selection, transform) in &selection {
-                if selection.selected() {
-                    if player.money >= button_state.cost {
-                        player.money -= button_state.cost;
-                        //Remove the base model/hitbox
-                        commands.entity(entity).despawn_recursive();
-                        spawn_tower(&mut commands, &assets, transform.translation, *tower_type);
-                    }
-                }
+            builder.build(*tower_type);
+        }
+    }
+}
+
+fn build_tower_hotkeys(controls: Controls, mut builder: TowerBuilder) {
+    for (action, tower_type) in [
+        (GameAction::BuildTomato, TowerType::Tomato),
+        (GameAction::BuildPotato, TowerType::Potato),
+        (GameAction::BuildCabbage, TowerType::Cabbage),
+    ] {
+        if controls.just_pressed(action) {
+            builder.build(tower_type);
+        }
+    }
+}
+
+/// Everything needed to manage the selected towers, shared by the tower panel's buttons and
+/// the hotkeys
+#[derive(SystemParam)]
+pub struct SelectedTowers<'w, 's> {
+    commands: Commands<'w, 's>,
+    towers: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Selection,
+            &'static mut Tower,
+            &'static mut TargetingMode,
+            &'static TowerType,
+            &'static Transform,
+            Option<&'static GridCell>,
+        ),
+    >,
+    player: Query<'w, 's, &'static mut Player>,
+    assets: Res<'w, GameAssets>,
+    picking: Res<'w, TowerPickingAssets>,
+    money_events: EventWriter<'w, 's, NotEnoughMoneyEvent>,
+}
+
+impl SelectedTowers<'_, '_> {
+    /// Upgrades every selected tower the player can pay for, fully upgraded ones stay as they are
+    pub fn upgrade(&mut self) {
+        let mut player = self.player.single_mut();
+
+        for (_, selection, mut tower, _, tower_type, ..) in &mut self.towers {
+            if !selection.selected() || tower.level >= MAX_TOWER_LEVEL {
+                continue;
+            }
+
+            if !upgrade_tower(&mut tower, *tower_type, &mut player) {
+                self.money_events.send(NotEnoughMoneyEvent);
+            }
+        }
+    }
+
+    pub fn cycle_targeting(&mut self) {
+        for (_, selection, _, mut targeting, ..) in &mut self.towers {
+            if selection.selected() {
+                *targeting = targeting.next();
+            }
+        }
+    }
+
+    pub fn sell(&mut self) {
+        let mut player = self.player.single_mut();
+
+        for (entity, selection, tower, _, _, transform, grid_cell) in &self.towers {
+            if !selection.selected() {
+                continue;
             }
+
+            sell_tower(
+                &mut self.commands,
+                &self.assets,
+                &self.picking,
+                &mut player,
+                entity,
+                &tower,
+                // Cells on the build grid are simply freed
+                grid_cell.is_none().then_some(transform.translation),
+            );
         }
     }
 }
 
-fn spawn_tower(
+fn selected_tower_hotkeys(controls: Controls, mut selected: SelectedTowers) {
+    if controls.just_pressed(GameAction::Upgrade) {
+        selected.upgrade();
+    }
+    if controls.just_pressed(GameAction::CycleTargeting) {
+        selected.cycle_targeting();
+    }
+    if controls.just_pressed(GameAction::Sell) {
+        selected.sell();
+    }
+}
+
+/// Raises the tower a level if the player can pay for it, towers at the top level can't be upgraded
+pub(crate) fn upgrade_tower(tower: &mut Tower, tower_type: TowerType, player: &mut Player) -> bool {
+    let cost = tower_type.upgrade_cost(tower.level);
+    if tower.level >= MAX_TOWER_LEVEL || player.money < cost {
+        return false;
+    }
+
+    player.money -= cost;
+    tower.invested += cost;
+    tower.level += 1;
+    let faster = tower.shooting_timer.duration().mul_f32(0.8);
+    tower.shooting_timer.set_duration(faster);
+    true
+}
+
+/// Refunds half of what was paid for the tower, and puts an empty base back at `base_position`
+pub(crate) fn sell_tower(
     commands: &mut Commands,
     assets: &GameAssets,
+    picking: &TowerPickingAssets,
+    player: &mut Player,
+    entity: Entity,
+    tower: &Tower,
+    base_position: Option<Vec3>,
+) {
+    player.money += tower.sell_value();
+    commands.entity(entity).despawn_recursive();
+    if let Some(position) = base_position {
+        spawn_tower_base(commands, assets, picking, position);
+    }
+}
+
+/// Collider mesh and highlight materials shared by everything that can be picked to build or manage towers
+#[derive(Resource)]
+pub struct TowerPickingAssets {
+    pub collider: Handle<Mesh>,
+    pub base_color: Handle<StandardMaterial>,
+    /// Built towers only show their collider while hovered or selected
+    pub tower_color: Handle<StandardMaterial>,
+    pub selected_color: Handle<StandardMaterial>,
+}
+
+impl FromWorld for TowerPickingAssets {
+    fn from_world(world: &mut World) -> Self {
+        let collider = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(shape::Capsule::default().into());
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            collider,
+            base_color: materials.add(Color::rgba(0.3, 0.5, 0.3, 0.3).into()),
+            tower_color: materials.add(Color::rgba(0.3, 0.5, 0.3, 0.0).into()),
+            selected_color: materials.add(Color::rgba(0.3, 0.9, 0.3, 0.9).into()),
+        }
+    }
+}
+
+impl TowerPickingAssets {
+    fn pickable(&self, initial: &Handle<StandardMaterial>) -> impl Bundle {
+        (
+            self.collider.clone(),
+            Highlighting {
+                initial: initial.clone(),
+                hovered: Some(self.selected_color.clone()),
+                pressed: Some(self.selected_color.clone()),
+                selected: Some(self.selected_color.clone()),
+            },
+            initial.clone(),
+            NotShadowCaster,
+            PickableBundle::default(),
+        )
+    }
+}
+
+pub fn spawn_tower_base(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    picking: &TowerPickingAssets,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn(SpatialBundle::from_transform(Transform::from_translation(
+            position,
+        )))
+        .insert(Name::new("Tower_Base"))
+        .insert(StateScoped(GameState::InGame))
+        .insert(TowerBase)
+        .insert(picking.pickable(&picking.base_color))
+        .with_children(|commands| {
+            commands.spawn(SceneBundle {
+                scene: assets.tower_base_scene.clone(),
+                transform: Transform::from_xyz(0.0, -TOWER_BASE_HEIGHT, 0.0),
+                ..Default::default()
+            });
+        })
+        .id()
+}
+
+pub fn spawn_tower(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    picking: &TowerPickingAssets,
     position: Vec3,
     tower_type: TowerType,
 ) -> Entity {
-    let (model, tower) = tower_type.get_tower(&assets);
+    let (model, tower) = tower_type.get_tower(assets);
 
     commands
         .spawn(SpatialBundle::from_transform(Transform::from_translation(
             position,
         )))
         .insert(Name::new(format!("{:?}_Tower", tower_type)))
+        .insert(StateScoped(GameState::InGame))
         .insert(tower_type)
         .insert(tower)
+        .insert(TargetingMode::default())
+        .insert(CombatStats::default())
+        .insert(picking.pickable(&picking.tower_color))
         .with_children(|commands| {
             commands.spawn(SceneBundle {
                 scene: model,
-                transform: Transform::from_xyz(0.0, -0.8, 0.0),
+                transform: Transform::from_xyz(0.0, -TOWER_BASE_HEIGHT, 0.0),
                 ..Default::default()
             });
         })
@@ -119,19 +402,22 @@ fn create_ui_on_selection(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     //Perf could probably be smarter with change detection
-    selections: Query<&Selection>,
+    selections: Query<&Selection, With<TowerBase>>,
     root: Query<Entity, With<TowerUIRoot>>,
+    level: Res<Level>,
 ) {
-    let at_least_one_selected = selections.iter().any(|selection| selection.selected());
+    // Levels with a build grid keep the buttons up to pick towers to place
+    let show_buttons =
+        level.build_grid.is_some() || selections.iter().any(|selection| selection.selected());
     match root.get_single() {
         Ok(root) => {
-            if !at_least_one_selected {
+            if !show_buttons {
                 commands.entity(root).despawn_recursive();
             }
         }
         //No root exist
         Err(QuerySingleError::NoEntities(..)) => {
-            if at_least_one_selected {
+            if show_buttons {
                 create_ui(&mut commands, &asset_server);
             }
         }
@@ -148,8 +434,6 @@ fn create_ui(commands: &mut Commands, asset_server: &AssetServer) {
 
     let towers = [TowerType::Tomato, TowerType::Potato, TowerType::Cabbage];
 
-    let costs = [50, 80, 110];
-
     commands
         .spawn(NodeBundle {
             style: Style {
@@ -160,6 +444,7 @@ fn create_ui(commands: &mut Commands, asset_server: &AssetServer) {
             ..default()
         })
         .insert(TowerUIRoot)
+        .insert(StateScoped(GameState::InGame))
         .with_children(|commands| {
             for i in 0..3 {
                 commands
@@ -175,7 +460,7 @@ fn create_ui(commands: &mut Commands, asset_server: &AssetServer) {
                     })
                     .insert(TowerButtonState {
                         affordable: false,
-                        cost: costs[i],
+                        cost: towers[i].cost(),
                     })
                     .insert(towers[i]);
             }
@@ -204,12 +489,19 @@ pub struct TowerPlugin;
 
 impl Plugin for TowerPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Tower>().add_system_set(
-            SystemSet::on_update(GameState::InGame)
-                .with_system(tower_shooting)
-                .with_system(tower_button_clicked)
-                .with_system(create_ui_on_selection)
-                .with_system(grey_tower_buttons.after(create_ui_on_selection)),
-        );
+        app.register_type::<Tower>()
+            .register_type::<TargetingMode>()
+            .init_resource::<TowerPickingAssets>()
+            .add_event::<TowerFiredEvent>()
+            .add_event::<NotEnoughMoneyEvent>()
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(tower_shooting)
+                    .with_system(tower_button_clicked)
+                    .with_system(build_tower_hotkeys)
+                    .with_system(selected_tower_hotkeys)
+                    .with_system(create_ui_on_selection)
+                    .with_system(grey_tower_buttons.after(create_ui_on_selection)),
+            );
     }
 }
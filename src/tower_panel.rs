@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+use bevy_mod_picking::Selection;
+
+use crate::components::{GameState, TargetingMode, Tower, TowerType, MAX_TOWER_LEVEL};
+use crate::localization::LocalizedText;
+use crate::player::Player;
+use crate::state_scoped::StateScoped;
+use crate::stats::CombatStats;
+use crate::tower::SelectedTowers;
+
+/// Details of the selected tower, shown in the top right corner
+#[derive(Component, Default)]
+pub struct TowerPanel {
+    /// What the panel shows right now, it is only rebuilt when this changes
+    pub lines: Vec<LocalizedText>,
+}
+
+/// Holds the text lines of the panel, so they can be rebuilt without touching the buttons
+#[derive(Component)]
+struct TowerPanelLines;
+
+/// The buttons at the bottom of the tower panel, acting on the tower shown in it
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TowerPanelButton {
+    Upgrade,
+    Targeting,
+    Sell,
+}
+
+impl TowerPanelButton {
+    pub const ALL: [TowerPanelButton; 3] = [
+        TowerPanelButton::Upgrade,
+        TowerPanelButton::Targeting,
+        TowerPanelButton::Sell,
+    ];
+
+    pub fn text_key(&self) -> &'static str {
+        match self {
+            TowerPanelButton::Upgrade => "tower-button-upgrade",
+            TowerPanelButton::Targeting => "tower-button-targeting",
+            TowerPanelButton::Sell => "tower-button-sell",
+        }
+    }
+}
+
+/// The lines of the panel for a tower
+pub fn tower_panel_lines(
+    tower_type: TowerType,
+    tower: &Tower,
+    targeting: TargetingMode,
+    stats: &CombatStats,
+) -> Vec<LocalizedText> {
+    let upgrade = if tower.level >= MAX_TOWER_LEVEL {
+        LocalizedText::new("tower-fully-upgraded")
+    } else {
+        LocalizedText::new("tower-upgrade-cost")
+            .with_arg("cost", tower_type.upgrade_cost(tower.level))
+    };
+
+    vec![
+        LocalizedText::new(tower_type.text_key()),
+        LocalizedText::new("tower-level")
+            .with_arg("level", tower.level)
+            .with_arg("max", MAX_TOWER_LEVEL),
+        LocalizedText::new(targeting.text_key()),
+        LocalizedText::new("tower-shots").with_arg("shots", stats.shots),
+        LocalizedText::new("tower-hits")
+            .with_arg("hits", stats.hits)
+            .with_arg("rate", format!("{:.0}", stats.hit_rate() * 100.0)),
+        LocalizedText::new("tower-damage").with_arg("damage", stats.damage),
+        LocalizedText::new("tower-kills").with_arg("kills", stats.kills),
+        upgrade,
+        LocalizedText::new("tower-sell-value").with_arg("money", tower.sell_value()),
+    ]
+}
+
+pub struct TowerPanelPlugin;
+
+impl Plugin for TowerPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(tower_panel_button_clicked)
+                .with_system(update_tower_panel.after(tower_panel_button_clicked))
+                .with_system(grey_upgrade_button.after(update_tower_panel)),
+        );
+    }
+}
+
+fn update_tower_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    towers: Query<(&Selection, &TowerType, &Tower, &TargetingMode, &CombatStats)>,
+    mut panels: Query<(Entity, &mut TowerPanel)>,
+    line_nodes: Query<Entity, With<TowerPanelLines>>,
+) {
+    let selected = towers.iter().find(|(selection, ..)| selection.selected());
+    let Some((_, tower_type, tower, targeting, stats)) = selected else {
+        for (panel, _) in &panels {
+            commands.entity(panel).despawn_recursive();
+        }
+        return;
+    };
+
+    let lines = tower_panel_lines(*tower_type, tower, *targeting, stats);
+    let line_node = match panels.get_single_mut() {
+        Ok((_, mut shown)) => {
+            if shown.lines == lines {
+                return;
+            }
+            shown.lines = lines.clone();
+            let Ok(line_node) = line_nodes.get_single() else {
+                return;
+            };
+            commands.entity(line_node).despawn_descendants();
+            line_node
+        }
+        Err(_) => spawn_tower_panel(&mut commands, &asset_server, lines.clone()),
+    };
+
+    commands.entity(line_node).with_children(|commands| {
+        for (index, line) in lines.into_iter().enumerate() {
+            // The tower's name heads the panel
+            let font_size = if index == 0 { 32.0 } else { 24.0 };
+            commands
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("FiraSans-Bold.ttf"),
+                            font_size,
+                            color: Color::BLACK,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(line);
+        }
+    });
+}
+
+/// Spawns the panel with its buttons, returning the node its lines go in
+fn spawn_tower_panel(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    lines: Vec<LocalizedText>,
+) -> Entity {
+    let mut line_node = None;
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(12.0),
+                    top: Val::Px(72.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.7).into(),
+            ..default()
+        })
+        .insert(TowerPanel { lines })
+        .insert(Name::new("Tower_Panel"))
+        .insert(StateScoped(GameState::InGame))
+        .with_children(|commands| {
+            line_node = Some(
+                commands
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(TowerPanelLines)
+                    .id(),
+            );
+
+            commands
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        margin: UiRect::top(Val::Px(8.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|commands| {
+                    for button in TowerPanelButton::ALL {
+                        commands
+                            .spawn(ButtonBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(112.0), Val::Px(36.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::all(Val::Px(2.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .insert(button)
+                            .with_children(|commands| {
+                                commands
+                                    .spawn(TextBundle::from_section(
+                                        "",
+                                        TextStyle {
+                                            font: asset_server.load("FiraSans-Bold.ttf"),
+                                            font_size: 22.0,
+                                            color: Color::BLACK,
+                                        },
+                                    ))
+                                    .insert(LocalizedText::new(button.text_key()));
+                            });
+                    }
+                });
+        });
+
+    line_node.expect("the panel spawns its line node")
+}
+
+/// Does what the buttons say to the selected towers, like the hotkeys do
+fn tower_panel_button_clicked(
+    buttons: Query<(&Interaction, &TowerPanelButton), Changed<Interaction>>,
+    mut selected: SelectedTowers,
+) {
+    for (interaction, button) in &buttons {
+        if !matches!(interaction, Interaction::Clicked) {
+            continue;
+        }
+
+        match button {
+            TowerPanelButton::Upgrade => selected.upgrade(),
+            TowerPanelButton::Targeting => selected.cycle_targeting(),
+            TowerPanelButton::Sell => selected.sell(),
+        }
+    }
+}
+
+/// Greys out the upgrade button while the selected tower can't be upgraded
+fn grey_upgrade_button(
+    mut buttons: Query<(&mut BackgroundColor, &TowerPanelButton)>,
+    towers: Query<(&Selection, &Tower, &TowerType)>,
+    player: Query<&Player>,
+) {
+    let Some((_, tower, tower_type)) = towers.iter().find(|(selection, ..)| selection.selected())
+    else {
+        return;
+    };
+    let player = player.single();
+    let upgradable =
+        tower.level < MAX_TOWER_LEVEL && player.money >= tower_type.upgrade_cost(tower.level);
+
+    for (mut tint, button) in &mut buttons {
+        if *button == TowerPanelButton::Upgrade {
+            *tint = if upgradable {
+                Color::WHITE.into()
+            } else {
+                Color::DARK_GRAY.into()
+            };
+        }
+    }
+}
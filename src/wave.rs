@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+use crate::components::{GameAssets, GameState, Target, WaveStartedEvent};
+use crate::controls::{Controls, GameAction};
+use crate::difficulty::Difficulty;
+use crate::game_speed::GameTime;
+use crate::level::Level;
+use crate::target::spawn_target;
+
+/// Height targets walk at
+pub const TARGET_HEIGHT: f32 = 0.4;
+
+/// Tracks which waves of the current level have been sent
+#[derive(Resource, Default)]
+pub struct WaveProgress {
+    /// Index of the next wave to spawn
+    pub next: usize,
+    pub countdown: Timer,
+}
+
+impl WaveProgress {
+    pub fn all_spawned(&self, level: &Level) -> bool {
+        self.next >= level.waves.len()
+    }
+}
+
+#[derive(Default)]
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaveProgress>()
+            .add_event::<WaveStartedEvent>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(start_waves))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(next_wave)
+                    .with_system(call_next_wave_early.before(next_wave))
+                    // Before any spawning, the targets of a wave sent this frame don't exist yet
+                    .with_system(check_victory.before(next_wave).before(call_next_wave_early)),
+            );
+    }
+}
+
+fn start_waves(
+    mut commands: Commands,
+    mut progress: ResMut<WaveProgress>,
+    level: Res<Level>,
+    assets: Res<GameAssets>,
+    difficulty: Res<Difficulty>,
+    mut wave_events: EventWriter<WaveStartedEvent>,
+) {
+    *progress = WaveProgress {
+        next: 0,
+        countdown: Timer::from_seconds(level.wave_delay, TimerMode::Once),
+    };
+
+    // The first wave doesn't wait
+    spawn_next_wave(
+        &mut commands,
+        &mut wave_events,
+        &mut progress,
+        &level,
+        &assets,
+        *difficulty,
+    );
+}
+
+fn next_wave(
+    mut commands: Commands,
+    mut progress: ResMut<WaveProgress>,
+    targets: Query<(), With<Target>>,
+    level: Res<Level>,
+    assets: Res<GameAssets>,
+    difficulty: Res<Difficulty>,
+    mut wave_events: EventWriter<WaveStartedEvent>,
+    time: GameTime,
+    mut last_seen: Local<usize>,
+) {
+    // A wave called early this frame has no targets yet, so the field only looks clear
+    if progress.next != *last_seen {
+        *last_seen = progress.next;
+        return;
+    }
+
+    if progress.all_spawned(&level) || !targets.is_empty() {
+        return;
+    }
+
+    progress.countdown.tick(time.delta());
+    if progress.countdown.just_finished() {
+        spawn_next_wave(
+            &mut commands,
+            &mut wave_events,
+            &mut progress,
+            &level,
+            &assets,
+            *difficulty,
+        );
+        *last_seen = progress.next;
+    }
+}
+
+/// Sends the next wave right away instead of waiting for the field to clear
+fn call_next_wave_early(
+    mut commands: Commands,
+    controls: Controls,
+    mut progress: ResMut<WaveProgress>,
+    level: Res<Level>,
+    assets: Res<GameAssets>,
+    difficulty: Res<Difficulty>,
+    mut wave_events: EventWriter<WaveStartedEvent>,
+) {
+    if controls.just_pressed(GameAction::NextWave) {
+        spawn_next_wave(
+            &mut commands,
+            &mut wave_events,
+            &mut progress,
+            &level,
+            &assets,
+            *difficulty,
+        );
+    }
+}
+
+fn check_victory(
+    progress: Res<WaveProgress>,
+    targets: Query<(), With<Target>>,
+    level: Res<Level>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if progress.all_spawned(&level) && targets.is_empty() {
+        info!("VICTORY");
+        game_state.set(GameState::Victory).unwrap();
+    }
+}
+
+fn spawn_next_wave(
+    commands: &mut Commands,
+    wave_events: &mut EventWriter<WaveStartedEvent>,
+    progress: &mut WaveProgress,
+    level: &Level,
+    assets: &GameAssets,
+    difficulty: Difficulty,
+) {
+    let Some(wave) = level.waves.get(progress.next) else {
+        return;
+    };
+
+    // Line the wave up behind the spawn, facing the first waypoint
+    let behind = level
+        .waypoints
+        .first()
+        .map(|first| (level.spawn - *first).normalize_or_zero())
+        .unwrap_or(Vec2::NEG_X);
+
+    for i in 1..=wave.count {
+        let distance = -wave.spacing * i as f32;
+        let position = level.spawn - behind * distance;
+        spawn_target(
+            commands,
+            assets,
+            Vec3::new(position.x, TARGET_HEIGHT, position.y),
+            distance,
+            difficulty.target_health(wave.health),
+            difficulty.target_speed(wave.speed),
+        );
+    }
+
+    info!("Wave {} of {}", progress.next + 1, level.waves.len());
+    wave_events.send(WaveStartedEvent {
+        wave: progress.next,
+    });
+    progress.next += 1;
+    progress.countdown.reset();
+}
@@ -0,0 +1,245 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use common::TestApp;
+use towerdefense::{
+    check_placement,
+    components::{Tower, TowerBase, TowerType},
+    BuildGrid, CursorGroundPosition, FlowField, FlowFieldArrow, GridCell, Level, Placement,
+    PlacementError, TowerLayout,
+};
+
+fn grid_level() -> Level {
+    Level {
+        floor_size: 10.0,
+        spawn: Vec2::new(-5.0, 0.5),
+        waypoints: vec![Vec2::new(5.0, 0.5)],
+        tower_slots: Vec::new(),
+        build_grid: Some(BuildGrid {
+            cell_size: 1.0,
+            path_clearance: 1.0,
+            mazing: false,
+        }),
+        ..Level::default()
+    }
+}
+
+fn point_at(test: &mut TestApp, position: Vec2) {
+    test.app.world.resource_mut::<CursorGroundPosition>().0 = Some(position);
+}
+
+#[test]
+fn cells_cover_the_floor() {
+    let grid = BuildGrid::default();
+
+    assert_eq!(grid.cells_per_side(10.0), 10);
+    assert_eq!(grid.cell_at(Vec2::new(-4.9, -4.9), 10.0), Some(IVec2::ZERO));
+    assert_eq!(
+        grid.cell_at(Vec2::new(4.9, 4.9), 10.0),
+        Some(IVec2::splat(9))
+    );
+    assert_eq!(grid.cell_at(Vec2::new(5.1, 0.0), 10.0), None);
+    assert_eq!(grid.cell_center(IVec2::ZERO, 10.0), Vec2::splat(-4.5));
+}
+
+#[test]
+fn placement_is_validated() {
+    let level = grid_level();
+    let mut occupied = HashSet::default();
+
+    assert_eq!(
+        check_placement(&level, Vec2::new(2.5, 3.5), &occupied, &[]),
+        Ok(IVec2::new(7, 8))
+    );
+    assert_eq!(
+        check_placement(&level, Vec2::new(2.5, 0.5), &occupied, &[]),
+        Err(PlacementError::OnPath)
+    );
+    assert_eq!(
+        check_placement(&level, Vec2::new(8.0, 3.5), &occupied, &[]),
+        Err(PlacementError::OutOfBounds)
+    );
+
+    occupied.insert(IVec2::new(7, 8));
+    assert_eq!(
+        check_placement(&level, Vec2::new(2.5, 3.5), &occupied, &[]),
+        Err(PlacementError::Occupied)
+    );
+
+    assert_eq!(
+        check_placement(&Level::default(), Vec2::ZERO, &occupied, &[]),
+        Err(PlacementError::NoGrid)
+    );
+}
+
+#[test]
+fn towers_are_placed_where_the_cursor_points() {
+    let mut test = TestApp::with_level(grid_level(), TowerLayout::default());
+
+    test.press_key(KeyCode::Key1);
+    test.step(1);
+    test.release_key(KeyCode::Key1);
+    assert_eq!(
+        test.app.world.resource::<Placement>().tower_type,
+        Some(TowerType::Tomato)
+    );
+
+    // Nothing is built on the path
+    point_at(&mut test, Vec2::new(0.0, 0.5));
+    test.click_mouse(MouseButton::Left);
+    assert_eq!(test.count::<With<Tower>>(), 0);
+
+    point_at(&mut test, Vec2::new(2.2, 3.7));
+    test.click_mouse(MouseButton::Left);
+
+    let tower = test.entities::<With<Tower>>()[0];
+    assert_eq!(
+        test.get::<GridCell>(tower),
+        Some(&GridCell(IVec2::new(7, 8)))
+    );
+    assert_eq!(test.get::<Transform>(tower).unwrap().translation.x, 2.5);
+    assert_eq!(test.player().money, 100 - TowerType::Tomato.cost());
+    assert_eq!(test.app.world.resource::<Placement>().tower_type, None);
+}
+
+#[test]
+fn grid_towers_stay_off_tower_slots() {
+    let level = Level {
+        tower_slots: vec![Vec2::new(2.5, 3.5)],
+        ..grid_level()
+    };
+    let mut test = TestApp::with_level(level, TowerLayout::default());
+    test.app.world.resource_mut::<Placement>().tower_type = Some(TowerType::Tomato);
+
+    point_at(&mut test, Vec2::new(2.5, 3.5));
+    test.click_mouse(MouseButton::Left);
+
+    assert_eq!(test.count::<With<Tower>>(), 0);
+    assert_eq!(test.count::<With<TowerBase>>(), 1);
+}
+
+#[test]
+fn selling_frees_the_cell() {
+    let mut test = TestApp::with_level(grid_level(), TowerLayout::default());
+    test.app.world.resource_mut::<Placement>().tower_type = Some(TowerType::Tomato);
+    point_at(&mut test, Vec2::new(2.5, 3.5));
+    test.click_mouse(MouseButton::Left);
+
+    let tower = test.entities::<With<Tower>>()[0];
+    test.select(tower);
+    test.step(1);
+    test.press_key(KeyCode::X);
+    test.step(1);
+
+    assert_eq!(test.count::<With<Tower>>(), 0);
+    assert_eq!(test.count::<With<GridCell>>(), 0);
+    assert_eq!(test.count::<With<TowerBase>>(), 0);
+}
+
+#[test]
+fn right_click_cancels_placement() {
+    let mut test = TestApp::with_level(grid_level(), TowerLayout::default());
+    test.app.world.resource_mut::<Placement>().tower_type = Some(TowerType::Potato);
+
+    test.click_mouse(MouseButton::Right);
+
+    assert_eq!(test.app.world.resource::<Placement>().tower_type, None);
+}
+
+/// A 4x4 grid crossed from the left to the right along the second row
+fn maze_level() -> Level {
+    Level {
+        floor_size: 4.0,
+        spawn: Vec2::new(-1.5, -0.5),
+        waypoints: vec![Vec2::new(1.5, -0.5)],
+        tower_slots: Vec::new(),
+        build_grid: Some(BuildGrid {
+            cell_size: 1.0,
+            path_clearance: 0.0,
+            mazing: true,
+        }),
+        ..Level::default()
+    }
+}
+
+#[test]
+fn mazing_rejects_towers_that_block_the_path() {
+    let level = maze_level();
+    let mut occupied: HashSet<IVec2> = HashSet::default();
+
+    // Towers can go on the current route, the targets walk around them
+    for y in 0..3 {
+        let cell = check_placement(&level, Vec2::new(0.5, y as f32 - 1.5), &occupied, &[]).unwrap();
+        occupied.insert(cell);
+    }
+
+    assert_eq!(
+        check_placement(&level, Vec2::new(0.5, 1.5), &occupied, &[]),
+        Err(PlacementError::BlocksPath)
+    );
+    assert_eq!(
+        check_placement(&level, level.spawn, &HashSet::default(), &[]),
+        Err(PlacementError::BlocksPath)
+    );
+}
+
+#[test]
+fn mazing_keeps_a_way_out_for_every_target() {
+    let level = maze_level();
+    let occupied: HashSet<IVec2> = [IVec2::new(2, 3)].into_iter().collect();
+    let in_the_corner = Vec2::new(1.5, 1.5);
+
+    // Walls in the corner, which the spawn's route doesn't need
+    assert!(check_placement(&level, Vec2::new(1.5, 0.5), &occupied, &[]).is_ok());
+    assert_eq!(
+        check_placement(&level, Vec2::new(1.5, 0.5), &occupied, &[in_the_corner]),
+        Err(PlacementError::BlocksPath)
+    );
+
+    // Nor can a tower go on top of a target
+    assert!(check_placement(&level, in_the_corner, &HashSet::default(), &[]).is_ok());
+    assert_eq!(
+        check_placement(&level, in_the_corner, &HashSet::default(), &[in_the_corner]),
+        Err(PlacementError::BlocksPath)
+    );
+}
+
+#[test]
+fn flow_field_leads_around_new_towers() {
+    let mut test = TestApp::with_level(maze_level(), TowerLayout::default());
+    let beside_spawn = Vec2::new(-0.5, -0.5);
+
+    let flow_field = test.app.world.resource::<FlowField>();
+    assert!(flow_field.is_active());
+    assert_eq!(flow_field.distance(IVec2::new(1, 1)), Some(2));
+    assert_eq!(flow_field.steer(beside_spawn), Some(Vec2::new(0.5, -0.5)));
+
+    test.app.world.resource_mut::<Placement>().tower_type = Some(TowerType::Tomato);
+    point_at(&mut test, Vec2::new(0.5, -0.5));
+    test.click_mouse(MouseButton::Left);
+    assert_eq!(test.count::<With<GridCell>>(), 1);
+
+    // Recomputed once for the new tower, every target steers by the same field
+    let flow_field = test.app.world.resource::<FlowField>();
+    assert_eq!(flow_field.distance(IVec2::new(1, 1)), Some(4));
+    assert_eq!(flow_field.distance(IVec2::new(2, 1)), None);
+    let next = flow_field.steer(beside_spawn).unwrap();
+    assert_ne!(next, Vec2::new(0.5, -0.5));
+    assert_eq!(next.x, -0.5);
+}
+
+#[test]
+fn flow_field_overlay_draws_an_arrow_per_cell() {
+    let mut test = TestApp::with_level(maze_level(), TowerLayout::default());
+    assert_eq!(test.count::<With<FlowFieldArrow>>(), 0);
+
+    test.press_key(KeyCode::F4);
+    test.step(1);
+    test.release_key(KeyCode::F4);
+    // Every cell but the exit points somewhere
+    assert_eq!(test.count::<With<FlowFieldArrow>>(), 15);
+
+    test.press_key(KeyCode::F4);
+    test.step(1);
+    assert_eq!(test.count::<With<FlowFieldArrow>>(), 0);
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use common::TestApp;
+use towerdefense::{
+    components::{Target, Tower, TowerBase},
+    minimap_to_world, world_to_minimap, Level, MinimapMarker, RtsCamera, MAX_ZOOM, MIN_ZOOM,
+    ROTATION_SNAP,
+};
+
+fn with_camera() -> (TestApp, Entity) {
+    let mut test = TestApp::new();
+    let camera = test
+        .app
+        .world
+        .spawn((RtsCamera::default(), Transform::default()))
+        .id();
+    (test, camera)
+}
+
+fn camera(test: &TestApp, entity: Entity) -> &RtsCamera {
+    test.get::<RtsCamera>(entity).unwrap()
+}
+
+fn scroll(test: &mut TestApp, lines: f32) {
+    test.app.world.send_event(MouseWheel {
+        unit: MouseScrollUnit::Line,
+        x: 0.0,
+        y: lines,
+    });
+    test.step(1);
+}
+
+#[test]
+fn panning_stops_at_the_edge_of_the_level() {
+    let (mut test, entity) = with_camera();
+
+    test.press_key(KeyCode::W);
+    test.step(2);
+    assert!(camera(&test, entity).focus.y > 0.0);
+
+    test.step_seconds(30.0);
+    let half_size = Level::default().floor_size / 2.0;
+    assert_eq!(camera(&test, entity).focus.y, half_size);
+
+    // The transform follows, looking down at the focus from behind
+    let transform = test.get::<Transform>(entity).unwrap();
+    assert!(transform.translation.y > 0.0);
+    assert!(transform.translation.z < half_size);
+}
+
+#[test]
+fn zoom_stays_between_limits() {
+    let (mut test, entity) = with_camera();
+
+    scroll(&mut test, 100.0);
+    assert_eq!(camera(&test, entity).zoom, MIN_ZOOM);
+
+    scroll(&mut test, -100.0);
+    assert_eq!(camera(&test, entity).zoom, MAX_ZOOM);
+}
+
+#[test]
+fn rotation_snaps_to_fixed_angles() {
+    let (mut test, entity) = with_camera();
+
+    test.press_key(KeyCode::E);
+    test.step(1);
+    test.release_key(KeyCode::E);
+    test.step_seconds(2.0);
+
+    assert_eq!(camera(&test, entity).rotation, 1);
+    assert_eq!(camera(&test, entity).yaw(), -ROTATION_SNAP);
+}
+
+#[test]
+fn focus_moves_to_the_selection() {
+    let (mut test, entity) = with_camera();
+    let base = test.entities::<With<TowerBase>>()[0];
+    test.select(base);
+    test.step(1);
+
+    test.press_key(KeyCode::F);
+    test.step(1);
+
+    let position = test.get::<GlobalTransform>(base).unwrap().translation();
+    assert_eq!(
+        camera(&test, entity).focus,
+        Vec2::new(position.x, position.z)
+    );
+}
+
+#[test]
+fn minimap_matches_the_starting_view() {
+    let floor_size = 20.0;
+
+    // Looking along +z the way the main camera starts, so -x is on the right
+    assert_eq!(
+        minimap_to_world(Vec2::new(1.0, 1.0), floor_size),
+        Vec2::new(-10.0, 10.0)
+    );
+    assert_eq!(minimap_to_world(Vec2::splat(0.5), floor_size), Vec2::ZERO);
+
+    let point = Vec2::new(3.0, -4.5);
+    let round_trip = minimap_to_world(world_to_minimap(point, floor_size), floor_size);
+    assert!(round_trip.distance(point) < 0.0001);
+}
+
+#[test]
+fn minimap_marks_targets_and_towers() {
+    let mut test = TestApp::with_level(Level::default(), "0=tomato".parse().unwrap());
+    test.step(1);
+
+    let expected = test.count::<With<Target>>() + test.count::<With<Tower>>();
+    assert_eq!(test.count::<With<MinimapMarker>>(), expected);
+
+    let target = test.entities::<With<Target>>()[0];
+    test.app.world.despawn(target);
+    test.step(2);
+    assert_eq!(test.count::<With<MinimapMarker>>(), expected - 1);
+}
+
+#[test]
+fn minimap_hides_markers_off_the_floor() {
+    let mut test = TestApp::new();
+    let target = test.entities::<With<Target>>()[0];
+    {
+        // Far back in the line behind the spawn, and left standing there
+        let mut target = test.get_mut::<Target>(target).unwrap();
+        target.speed = 0.0;
+        target.distance = -100.0;
+    }
+    test.step(2);
+
+    let display = |test: &mut TestApp| {
+        let marker = test
+            .entities::<With<MinimapMarker>>()
+            .into_iter()
+            .find(|marker| test.get::<MinimapMarker>(*marker).unwrap().0 == target)
+            .expect("target has no marker");
+        test.get::<Style>(marker).unwrap().display
+    };
+    assert_eq!(display(&mut test), Display::None);
+
+    test.get_mut::<Target>(target).unwrap().distance = 1.0;
+    test.step(2);
+    assert_eq!(display(&mut test), Display::Flex);
+}
@@ -0,0 +1,166 @@
+//! Support for driving the gameplay plugins from tests, without a window or GPU.
+
+use bevy::{
+    ecs::query::ReadOnlyWorldQuery,
+    input::{keyboard::KeyboardInput, mouse::MouseButtonInput, ButtonState},
+    prelude::*,
+};
+use bevy_mod_picking::Selection;
+use towerdefense::{
+    components::GameState, headless::headless_app, Difficulty, Level, PlaySoundEvent, Player,
+    SoundCue, StateScoped, TowerLayout,
+};
+
+pub struct TestApp {
+    pub app: App,
+}
+
+#[allow(dead_code)]
+impl TestApp {
+    /// The tutorial level with no towers built, already in `GameState::InGame`
+    pub fn new() -> Self {
+        Self::with_level(Level::default(), TowerLayout::default())
+    }
+
+    pub fn with_level(level: Level, layout: TowerLayout) -> Self {
+        let mut test = Self {
+            app: headless_app(level, layout),
+        };
+        // Run the startup systems and spawn the scene
+        test.step(1);
+        test
+    }
+
+    /// The tutorial level played on `difficulty`
+    pub fn with_difficulty(difficulty: Difficulty) -> Self {
+        let mut test = Self {
+            app: headless_app(Level::default(), TowerLayout::default()),
+        };
+        test.app.insert_resource(difficulty);
+        test.step(1);
+        test
+    }
+
+    /// Runs `ticks` updates of 1/60th of a second each
+    pub fn step(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    pub fn step_seconds(&mut self, seconds: f32) {
+        self.step((seconds * 60.0).ceil() as usize);
+    }
+
+    pub fn state(&self) -> GameState {
+        self.app
+            .world
+            .resource::<State<GameState>>()
+            .current()
+            .clone()
+    }
+
+    pub fn player(&mut self) -> &Player {
+        self.app.world.query::<&Player>().single(&self.app.world)
+    }
+
+    pub fn player_mut(&mut self) -> Mut<Player> {
+        self.app
+            .world
+            .query::<&mut Player>()
+            .single_mut(&mut self.app.world)
+    }
+
+    /// Number of entities matching the query filter `F`
+    pub fn count<F: ReadOnlyWorldQuery + 'static>(&mut self) -> usize {
+        self.entities::<F>().len()
+    }
+
+    /// Number of entities that will be despawned when leaving `state`
+    pub fn scoped_to(&mut self, state: GameState) -> usize {
+        self.app
+            .world
+            .query::<&StateScoped<GameState>>()
+            .iter(&self.app.world)
+            .filter(|scope| scope.0 == state)
+            .count()
+    }
+
+    /// Sound cues asked for since the last call, from the last two updates at most
+    pub fn take_sounds(&mut self) -> Vec<SoundCue> {
+        self.app
+            .world
+            .resource_mut::<Events<PlaySoundEvent>>()
+            .drain()
+            .map(|event| event.0)
+            .collect()
+    }
+
+    pub fn entities<F: ReadOnlyWorldQuery + 'static>(&mut self) -> Vec<Entity> {
+        self.app
+            .world
+            .query_filtered::<Entity, F>()
+            .iter(&self.app.world)
+            .collect()
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.app.world.get::<T>(entity)
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<T>> {
+        self.app.world.get_mut::<T>(entity)
+    }
+
+    /// Marks a pickable entity as selected, as if it had been clicked in the world
+    pub fn select(&mut self, entity: Entity) {
+        self.get_mut::<Selection>(entity)
+            .expect("entity is not pickable")
+            .set_selected(true);
+    }
+
+    /// Clicks a UI button
+    pub fn click(&mut self, entity: Entity) {
+        *self
+            .get_mut::<Interaction>(entity)
+            .expect("entity is not a button") = Interaction::Clicked;
+    }
+
+    pub fn press_key(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Pressed);
+    }
+
+    pub fn release_key(&mut self, key: KeyCode) {
+        self.send_key(key, ButtonState::Released);
+    }
+
+    fn send_key(&mut self, key: KeyCode, state: ButtonState) {
+        self.app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key),
+            state,
+        });
+    }
+
+    pub fn press_mouse(&mut self, button: MouseButton) {
+        self.app.world.send_event(MouseButtonInput {
+            button,
+            state: ButtonState::Pressed,
+        });
+    }
+
+    pub fn release_mouse(&mut self, button: MouseButton) {
+        self.app.world.send_event(MouseButtonInput {
+            button,
+            state: ButtonState::Released,
+        });
+    }
+
+    /// Presses and releases a mouse button over two updates
+    pub fn click_mouse(&mut self, button: MouseButton) {
+        self.press_mouse(button);
+        self.step(1);
+        self.release_mouse(button);
+        self.step(1);
+    }
+}
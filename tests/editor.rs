@@ -0,0 +1,150 @@
+mod common;
+
+use std::env;
+
+use bevy::prelude::*;
+use common::TestApp;
+use towerdefense::{
+    components::{GameState, Target},
+    CursorGroundPosition, EditedLevel, EditorHandle, EditorPoint, EditorTool, GameAction,
+    KeyBindings, Level, LevelEditor,
+};
+
+fn in_editor() -> TestApp {
+    let mut test = TestApp::new();
+    test.app
+        .world
+        .resource_mut::<State<GameState>>()
+        .set(GameState::Editor)
+        .unwrap();
+    test.step(2);
+    test
+}
+
+fn point_at(test: &mut TestApp, position: Vec2) {
+    test.app.world.resource_mut::<CursorGroundPosition>().0 = Some(position);
+}
+
+fn level(test: &TestApp) -> &Level {
+    &test.app.world.resource::<EditedLevel>().0
+}
+
+fn tap_key(test: &mut TestApp, key: KeyCode) {
+    test.press_key(key);
+    test.step(1);
+    test.release_key(key);
+    test.step(1);
+}
+
+#[test]
+fn clicking_the_floor_adds_points() {
+    let mut test = in_editor();
+    let waypoints = level(&test).waypoints.len();
+    let editor_points = test.count::<With<EditorPoint>>();
+
+    point_at(&mut test, Vec2::new(-8.0, 3.0));
+    test.click_mouse(MouseButton::Left);
+    assert_eq!(level(&test).waypoints.len(), waypoints + 1);
+    assert_eq!(level(&test).waypoints.last(), Some(&Vec2::new(-8.0, 3.0)));
+
+    test.app.world.resource_mut::<LevelEditor>().tool = EditorTool::TowerSlots;
+    let slots = level(&test).tower_slots.len();
+    point_at(&mut test, Vec2::new(-8.0, -3.0));
+    test.click_mouse(MouseButton::Left);
+    assert_eq!(level(&test).tower_slots.len(), slots + 1);
+
+    // The scene is redrawn with the new points
+    assert_eq!(test.count::<With<EditorPoint>>(), editor_points + 2);
+}
+
+#[test]
+fn points_can_be_dragged_and_removed() {
+    let mut test = in_editor();
+    let first = level(&test).waypoints[0];
+    let waypoints = level(&test).waypoints.len();
+
+    point_at(&mut test, first + Vec2::new(0.2, 0.0));
+    test.press_mouse(MouseButton::Left);
+    test.step(1);
+    point_at(&mut test, Vec2::new(-4.0, -4.0));
+    test.step(1);
+    test.release_mouse(MouseButton::Left);
+    test.step(1);
+
+    assert_eq!(level(&test).waypoints.len(), waypoints);
+    assert_eq!(level(&test).waypoints[0], Vec2::new(-4.0, -4.0));
+    assert_eq!(
+        EditorHandle::at(level(&test), EditorTool::Waypoints, Vec2::new(-4.1, -4.0)),
+        Some(EditorHandle {
+            tool: EditorTool::Waypoints,
+            index: 0
+        })
+    );
+
+    test.click_mouse(MouseButton::Right);
+    assert_eq!(level(&test).waypoints.len(), waypoints - 1);
+    assert!(!level(&test).waypoints.contains(&Vec2::new(-4.0, -4.0)));
+}
+
+#[test]
+fn playtest_plays_the_edited_level_and_returns() {
+    let mut test = in_editor();
+    test.app.world.resource_mut::<EditedLevel>().0.waves[0].count = 3;
+
+    tap_key(&mut test, KeyCode::F5);
+    assert_eq!(test.state(), GameState::InGame);
+    assert_eq!(test.count::<With<Target>>(), 3);
+
+    tap_key(&mut test, KeyCode::F5);
+    assert_eq!(test.state(), GameState::Editor);
+    assert_eq!(test.count::<With<Target>>(), 0);
+    assert_eq!(level(&test).waves[0].count, 3);
+    assert_eq!(test.app.world.resource::<Level>().waves[0].count, 3);
+}
+
+#[test]
+fn playtest_key_can_be_rebound() {
+    let mut test = in_editor();
+    test.app
+        .world
+        .resource_mut::<KeyBindings>()
+        .bind(GameAction::Playtest, KeyCode::P)
+        .unwrap();
+
+    tap_key(&mut test, KeyCode::F5);
+    assert_eq!(test.state(), GameState::Editor);
+
+    tap_key(&mut test, KeyCode::P);
+    assert_eq!(test.state(), GameState::InGame);
+}
+
+#[test]
+fn levels_without_a_path_are_not_playtested() {
+    let mut test = in_editor();
+    test.app
+        .world
+        .resource_mut::<EditedLevel>()
+        .0
+        .waypoints
+        .clear();
+
+    tap_key(&mut test, KeyCode::F5);
+    assert_eq!(test.state(), GameState::Editor);
+    assert!(test.app.world.resource::<LevelEditor>().status.is_some());
+    // The level the main menu starts is left alone
+    assert!(!test.app.world.resource::<Level>().waypoints.is_empty());
+}
+
+#[test]
+fn exported_levels_load_back() {
+    let mut level = Level::default();
+    level.name = "Exported".to_string();
+    level.waypoints.push(Vec2::new(-3.0, 7.5));
+
+    let path = env::temp_dir().join(format!("exported_level_{}.ron", std::process::id()));
+    level.save(&path).unwrap();
+    let loaded = Level::load(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(loaded, level);
+}
@@ -0,0 +1,855 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    window::{ReceivedCharacter, WindowId},
+};
+use bevy_mod_picking::Selection;
+use common::TestApp;
+use towerdefense::{
+    components::{
+        Bullet, GameState, Health, Target, TargetDeathEvent, TargetingMode, Tower, TowerBase,
+        TowerButtonState, TowerType, MAX_TOWER_LEVEL,
+    },
+    CombatStats, ControlsButton, ControlsStatusText, Difficulty, EndScreenUIRoot, GameAction,
+    GameSpeed, HighScore, HighScores, KeyBindingButton, KeyBindings, Language, Level, Localization,
+    LocalizedText, MainMenuButton, MatchStats, MoneyUI, NameEntry, PauseMenuUIRoot,
+    PlayAgainButton, Player, RestartButton, SettingOption, SettingStepButton, Settings,
+    SettingsButton, SettingsUIRoot, SoundCatalogue, SoundCue, StringTable, TowerLayout, TowerPanel,
+    TowerPanelButton, Wave, WaveProgress, LOCALES_DIR, MAX_HIGH_SCORES, PAR_TIME_SECONDS,
+    SOUND_CATALOGUE_FILE, TIME_BONUS_PER_SECOND,
+};
+
+/// A short path right next to the spawn, so targets leak within a few ticks
+fn leaking_level(count: u32) -> Level {
+    Level {
+        spawn: Vec2::ZERO,
+        waypoints: vec![Vec2::new(0.5, 0.0)],
+        waves: vec![Wave {
+            count,
+            health: 3,
+            speed: 5.0,
+            spacing: 0.5,
+        }],
+        ..Level::default()
+    }
+}
+
+fn tap_key(test: &mut TestApp, key: KeyCode) {
+    test.press_key(key);
+    test.step(1);
+    test.release_key(key);
+    test.step(1);
+}
+
+fn tower_button(test: &mut TestApp, tower_type: TowerType) -> Entity {
+    test.entities::<With<TowerButtonState>>()
+        .into_iter()
+        .find(|button| test.get::<TowerType>(*button) == Some(&tower_type))
+        .expect("tower button missing")
+}
+
+#[test]
+fn first_wave_spawns_on_start() {
+    let mut test = TestApp::new();
+
+    assert_eq!(test.count::<With<Target>>(), 24);
+    assert_eq!(test.player().money, 100);
+    assert_eq!(test.player().health, 10);
+}
+
+#[test]
+fn targets_move_towards_first_waypoint() {
+    let mut test = TestApp::new();
+    let target = test.entities::<With<Target>>()[0];
+    let waypoint = Level::default().waypoints[0];
+    let distance = |test: &TestApp| {
+        let translation = test.get::<Transform>(target).unwrap().translation;
+        Vec2::new(translation.x, translation.z).distance(waypoint)
+    };
+
+    let before = distance(&test);
+    test.step_seconds(1.0);
+    let after = distance(&test);
+
+    assert!(
+        after < before,
+        "target did not move: {} -> {}",
+        before,
+        after
+    );
+}
+
+#[test]
+fn leaking_targets_hurt_player() {
+    let mut test = TestApp::with_level(leaking_level(3), TowerLayout::default());
+
+    test.step_seconds(2.0);
+
+    assert_eq!(test.count::<With<Target>>(), 0);
+    assert_eq!(test.player().health, 7);
+}
+
+#[test]
+fn losing_all_health_ends_the_game() {
+    let mut test = TestApp::with_level(leaking_level(3), TowerLayout::default());
+    test.player_mut().health = 2;
+
+    test.step_seconds(2.0);
+
+    assert_eq!(test.player().health, 0);
+    assert_eq!(test.state(), GameState::GameOver);
+}
+
+#[test]
+fn killing_a_target_gives_money() {
+    let mut test = TestApp::new();
+    let target = test.entities::<With<Target>>()[0];
+
+    test.get_mut::<Health>(target).unwrap().value = 0;
+    test.step(2);
+
+    assert_eq!(test.count::<With<Target>>(), 23);
+    assert_eq!(test.player().money, 110);
+}
+
+#[test]
+fn clicking_tower_button_builds_on_selected_base() {
+    let mut test = TestApp::new();
+    let bases = test.count::<With<TowerBase>>();
+    let base = test.entities::<With<TowerBase>>()[0];
+
+    test.select(base);
+    test.step(2);
+    let button = tower_button(&mut test, TowerType::Tomato);
+    test.click(button);
+    test.step(2);
+
+    assert_eq!(test.count::<With<Tower>>(), 1);
+    assert_eq!(test.count::<With<TowerBase>>(), bases - 1);
+    assert_eq!(test.player().money, 50);
+}
+
+#[test]
+fn towers_cannot_be_built_without_money() {
+    let mut test = TestApp::new();
+    test.player_mut().money = 10;
+    let base = test.entities::<With<TowerBase>>()[0];
+
+    test.select(base);
+    test.step(2);
+    let button = tower_button(&mut test, TowerType::Tomato);
+    test.click(button);
+    test.step(2);
+
+    assert_eq!(test.count::<With<Tower>>(), 0);
+    assert_eq!(test.player().money, 10);
+    assert!(test.take_sounds().contains(&SoundCue::NotEnoughMoney));
+}
+
+#[test]
+fn layout_towers_are_built_at_start() {
+    let layout = "0=tomato,1=cabbage".parse().unwrap();
+    let mut test = TestApp::with_level(Level::default(), layout);
+
+    assert_eq!(test.count::<With<Tower>>(), 2);
+    assert_eq!(
+        test.count::<With<TowerBase>>(),
+        Level::default().tower_slots.len() - 2
+    );
+}
+
+#[test]
+fn escape_pauses_and_resumes() {
+    let mut test = TestApp::new();
+    let target = test.entities::<With<Target>>()[0];
+
+    tap_key(&mut test, KeyCode::Escape);
+    assert_eq!(test.state(), GameState::Paused);
+
+    let paused_at = test.get::<Transform>(target).unwrap().translation;
+    test.step_seconds(1.0);
+    assert_eq!(
+        test.get::<Transform>(target).unwrap().translation,
+        paused_at
+    );
+
+    tap_key(&mut test, KeyCode::Escape);
+    assert_eq!(test.state(), GameState::InGame);
+    assert_eq!(test.count::<With<PauseMenuUIRoot>>(), 0);
+
+    test.step_seconds(1.0);
+    assert_ne!(
+        test.get::<Transform>(target).unwrap().translation,
+        paused_at
+    );
+}
+
+#[test]
+fn restart_from_pause_starts_a_fresh_match() {
+    let mut test = TestApp::new();
+    let target = test.entities::<With<Target>>()[0];
+    test.get_mut::<Health>(target).unwrap().value = 0;
+    test.step(2);
+    assert_eq!(test.player().money, 110);
+
+    tap_key(&mut test, KeyCode::Escape);
+    let restart = test.entities::<With<RestartButton>>()[0];
+    test.click(restart);
+    test.step(3);
+
+    assert_eq!(test.state(), GameState::InGame);
+    assert_eq!(test.count::<With<Player>>(), 1);
+    assert_eq!(test.count::<With<Target>>(), 24);
+    assert_eq!(test.count::<With<RestartButton>>(), 0);
+    assert_eq!(test.player().money, 100);
+}
+
+#[test]
+fn game_speed_scales_target_movement() {
+    let distance_moved = |speed: GameSpeed| {
+        let mut test = TestApp::new();
+        *test.app.world.resource_mut::<GameSpeed>() = speed;
+        let target = test.entities::<With<Target>>()[0];
+        let start = test.get::<Transform>(target).unwrap().translation;
+        test.step_seconds(1.0);
+        test.get::<Transform>(target)
+            .unwrap()
+            .translation
+            .distance(start)
+    };
+
+    let normal = distance_moved(GameSpeed::Normal);
+    let quadruple = distance_moved(GameSpeed::Quadruple);
+
+    assert!(
+        (quadruple / normal - 4.0).abs() < 0.1,
+        "{} vs {}",
+        normal,
+        quadruple
+    );
+}
+
+#[test]
+fn fast_bullets_stop_at_the_first_target_they_touch() {
+    let mut test = TestApp::new();
+    *test.app.world.resource_mut::<GameSpeed>() = GameSpeed::Quadruple;
+    let targets = test.entities::<With<Target>>();
+    for other in &targets[1..] {
+        test.app.world.despawn(*other);
+    }
+    test.get_mut::<Target>(targets[0]).unwrap().speed = 0.0;
+    let position = test
+        .get::<GlobalTransform>(targets[0])
+        .unwrap()
+        .translation();
+
+    // The fastest bullet covers more than a target's width in one frame at this speed
+    let bullet = test
+        .app
+        .world
+        .spawn((
+            Bullet {
+                direction: Vec3::X,
+                speed: 8.5,
+                damage: 0,
+                source: None,
+            },
+            Transform::from_translation(position - Vec3::X * 0.3),
+        ))
+        .id();
+    test.step(1);
+
+    let stopped_at = test.get::<Transform>(bullet).unwrap().translation;
+    assert!(stopped_at.distance(position) < 0.2, "{}", stopped_at);
+}
+
+#[test]
+fn function_keys_switch_game_speed() {
+    let mut test = TestApp::new();
+
+    tap_key(&mut test, KeyCode::F2);
+    assert_eq!(*test.app.world.resource::<GameSpeed>(), GameSpeed::Double);
+
+    tap_key(&mut test, KeyCode::F1);
+    assert_eq!(*test.app.world.resource::<GameSpeed>(), GameSpeed::Normal);
+}
+
+#[test]
+fn game_speed_keys_can_be_rebound() {
+    let mut test = TestApp::new();
+    test.app
+        .world
+        .resource_mut::<KeyBindings>()
+        .bind(GameAction::SpeedDouble, KeyCode::B)
+        .unwrap();
+
+    tap_key(&mut test, KeyCode::F2);
+    assert_eq!(*test.app.world.resource::<GameSpeed>(), GameSpeed::Normal);
+
+    tap_key(&mut test, KeyCode::B);
+    assert_eq!(*test.app.world.resource::<GameSpeed>(), GameSpeed::Double);
+}
+
+#[test]
+fn game_over_tears_down_the_match() {
+    let mut test = TestApp::with_level(leaking_level(3), TowerLayout::default());
+    test.player_mut().health = 1;
+
+    test.step_seconds(2.0);
+
+    assert_eq!(test.state(), GameState::GameOver);
+    assert_eq!(test.scoped_to(GameState::InGame), 0);
+    assert_eq!(test.count::<With<EndScreenUIRoot>>(), 1);
+}
+
+#[test]
+fn play_again_after_game_over() {
+    let mut test = TestApp::with_level(leaking_level(3), TowerLayout::default());
+    test.player_mut().health = 1;
+    test.step_seconds(2.0);
+
+    let play_again = test.entities::<With<PlayAgainButton>>()[0];
+    test.click(play_again);
+    test.step(2);
+
+    assert_eq!(test.state(), GameState::InGame);
+    assert_eq!(test.count::<With<EndScreenUIRoot>>(), 0);
+    assert_eq!(test.count::<With<Player>>(), 1);
+    assert_eq!(test.player().health, 10);
+}
+
+#[test]
+fn main_menu_after_game_over() {
+    let mut test = TestApp::with_level(leaking_level(3), TowerLayout::default());
+    test.player_mut().health = 1;
+    test.step_seconds(2.0);
+
+    let main_menu = test.entities::<With<MainMenuButton>>()[0];
+    test.click(main_menu);
+    test.step(2);
+
+    assert_eq!(test.state(), GameState::MainMenu);
+    assert_eq!(test.count::<With<EndScreenUIRoot>>(), 0);
+}
+
+#[test]
+fn clearing_the_last_wave_is_a_victory() {
+    let mut test = TestApp::with_level(leaking_level(2), TowerLayout::default());
+
+    for target in test.entities::<With<Target>>() {
+        test.get_mut::<Health>(target).unwrap().value = 0;
+    }
+    test.step(3);
+
+    assert_eq!(test.state(), GameState::Victory);
+    assert_eq!(test.scoped_to(GameState::InGame), 0);
+    assert_eq!(test.count::<With<EndScreenUIRoot>>(), 1);
+
+    let stats = test.app.world.resource::<MatchStats>();
+    assert_eq!(stats.kills, 2);
+    assert_eq!(stats.lives_left, 10);
+    assert_eq!(stats.stars(), 3);
+}
+
+#[test]
+fn score_counts_kills_lives_money_and_time() {
+    let mut stats = MatchStats {
+        kills: 5,
+        lives_left: 2,
+        money_left: 30,
+        duration: Duration::from_secs(PAR_TIME_SECONDS as u64 - 10),
+        ..MatchStats::default()
+    };
+    assert_eq!(stats.score(), 50 + 200 + 30 + 10 * TIME_BONUS_PER_SECOND);
+
+    // Slow wins and losses get no time bonus
+    stats.duration = Duration::from_secs_f32(PAR_TIME_SECONDS * 2.0);
+    assert_eq!(stats.score(), 280);
+    stats.lives_left = 0;
+    stats.duration = Duration::ZERO;
+    assert_eq!(stats.score(), 80);
+}
+
+#[test]
+fn high_scores_keep_the_best_per_level() {
+    let mut high_scores = HighScores::default();
+    let entry = |score| HighScore {
+        name: format!("player {}", score),
+        score,
+    };
+
+    for score in 1..=MAX_HIGH_SCORES as u32 {
+        assert!(high_scores.qualifies("Tutorial", score));
+        high_scores.insert("Tutorial", entry(score));
+    }
+    assert_eq!(
+        high_scores.level("Tutorial")[0].score,
+        MAX_HIGH_SCORES as u32
+    );
+
+    // A full table only takes scores that beat one on it
+    assert!(!high_scores.qualifies("Tutorial", 1));
+    assert_eq!(high_scores.insert("Tutorial", entry(1)), None);
+    assert_eq!(high_scores.insert("Tutorial", entry(5)), Some(6));
+    assert_eq!(high_scores.level("Tutorial").len(), MAX_HIGH_SCORES);
+    assert_eq!(high_scores.level("Tutorial").last().unwrap().score, 2);
+
+    assert!(high_scores.level("Maze").is_empty());
+}
+
+#[test]
+fn high_score_names_are_typed_on_the_end_screen() {
+    let mut test = TestApp::with_level(leaking_level(2), TowerLayout::default());
+    for target in test.entities::<With<Target>>() {
+        test.get_mut::<Health>(target).unwrap().value = 0;
+    }
+    test.step(3);
+    assert_eq!(test.state(), GameState::Victory);
+    assert_eq!(
+        test.app.world.resource::<NameEntry>().name,
+        Some(String::new())
+    );
+
+    for char in "Ann\u{8}a".chars() {
+        test.app.world.send_event(ReceivedCharacter {
+            id: WindowId::primary(),
+            char,
+        });
+    }
+    test.step(1);
+    tap_key(&mut test, KeyCode::Back);
+    tap_key(&mut test, KeyCode::Return);
+
+    let score = test.app.world.resource::<MatchStats>().score();
+    let high_scores = test.app.world.resource::<HighScores>();
+    assert_eq!(
+        high_scores.level(&Level::default().name),
+        &[HighScore {
+            name: "Ann".to_string(),
+            score
+        }]
+    );
+    assert_eq!(test.app.world.resource::<NameEntry>().rank, Some(0));
+}
+
+#[test]
+fn difficulty_scales_targets_and_starting_resources() {
+    let mut test = TestApp::with_difficulty(Difficulty::Hard);
+
+    assert_eq!(test.player().money, 80);
+    assert_eq!(test.player().health, 5);
+
+    let target = test.entities::<With<Target>>()[0];
+    assert_eq!(test.get::<Health>(target).unwrap().value, 5);
+
+    test.get_mut::<Health>(target).unwrap().value = 0;
+    test.step(2);
+    assert_eq!(test.player().money, 88);
+}
+
+#[test]
+fn settings_open_from_pause_menu() {
+    let mut test = TestApp::new();
+    tap_key(&mut test, KeyCode::Escape);
+
+    let settings = test.entities::<With<SettingsButton>>()[0];
+    test.click(settings);
+    test.step(1);
+    assert_eq!(test.state(), GameState::Settings);
+
+    let quieter = test
+        .entities::<With<SettingStepButton>>()
+        .into_iter()
+        .find(|&button| {
+            let button = test.get::<SettingStepButton>(button).unwrap();
+            button.option == SettingOption::MasterVolume && button.step < 0
+        })
+        .unwrap();
+    test.click(quieter);
+    test.step(1);
+    assert!((test.app.world.resource::<Settings>().master_volume - 0.9).abs() < 0.001);
+
+    // Back to the pause menu, which was left open underneath
+    tap_key(&mut test, KeyCode::Escape);
+    assert_eq!(test.state(), GameState::Paused);
+    assert_eq!(test.count::<With<SettingsUIRoot>>(), 0);
+    assert_eq!(test.count::<With<PauseMenuUIRoot>>(), 1);
+}
+
+#[test]
+fn game_events_cue_sounds() {
+    let mut test = TestApp::new();
+    assert!(test.take_sounds().contains(&SoundCue::WaveStarted));
+
+    let target = test.entities::<With<Target>>()[0];
+    test.get_mut::<Health>(target).unwrap().value = 0;
+    test.step(2);
+    assert!(test.take_sounds().contains(&SoundCue::TargetKilled));
+}
+
+#[test]
+fn sound_catalogue_files_exist() {
+    let catalogue =
+        SoundCatalogue::load(std::path::Path::new("assets").join(SOUND_CATALOGUE_FILE)).unwrap();
+
+    assert!(!catalogue.cues.is_empty());
+    for entry in catalogue.cues.values() {
+        let path = std::path::Path::new("assets").join(&entry.file);
+        assert!(path.exists(), "{} is missing", path.display());
+    }
+}
+
+/// Steps until the string tables are loaded and `language` is shown
+fn wait_for_language(test: &mut TestApp, language: Language) {
+    for _ in 0..500 {
+        if test.app.world.resource::<Localization>().language() == Some(language) {
+            return;
+        }
+        // The tables are read on the IO threads
+        std::thread::sleep(Duration::from_millis(10));
+        test.step(1);
+    }
+    panic!("{:?} never loaded", language);
+}
+
+#[test]
+fn hud_follows_the_language_setting() {
+    let mut test = TestApp::new();
+    let money = test.entities::<With<MoneyUI>>()[0];
+    wait_for_language(&mut test, Language::English);
+    test.step(1);
+    assert_eq!(
+        test.get::<Text>(money).unwrap().sections[0].value,
+        "Money: 100"
+    );
+
+    test.app.world.resource_mut::<Settings>().language = Language::German;
+    wait_for_language(&mut test, Language::German);
+    test.step(1);
+    assert_eq!(
+        test.get::<Text>(money).unwrap().sections[0].value,
+        "Geld: 100"
+    );
+}
+
+#[test]
+fn every_language_has_every_message() {
+    let table = |language: Language| {
+        let file = format!("{}.ftl", language.code());
+        StringTable::load(std::path::Path::new("assets").join(LOCALES_DIR).join(file)).unwrap()
+    };
+    let english = table(Language::English);
+
+    for language in Language::ALL {
+        let translated = table(language);
+        for key in english.keys() {
+            assert!(
+                translated.get(key).is_some(),
+                "{:?} is missing {}",
+                language,
+                key
+            );
+        }
+    }
+}
+
+/// The tutorial level with a tomato tower built on the first slot and selected
+fn selected_tomato_tower() -> (TestApp, Entity) {
+    let mut test = TestApp::with_level(Level::default(), "0=tomato".parse().unwrap());
+    let tower = test.entities::<With<Tower>>()[0];
+    test.select(tower);
+    test.step(1);
+    (test, tower)
+}
+
+#[test]
+fn number_keys_build_on_selected_base() {
+    let mut test = TestApp::new();
+    let base = test.entities::<With<TowerBase>>()[0];
+
+    test.select(base);
+    test.step(1);
+    tap_key(&mut test, KeyCode::Key2);
+
+    let tower = test.entities::<With<Tower>>()[0];
+    assert_eq!(*test.get::<TowerType>(tower).unwrap(), TowerType::Potato);
+    assert_eq!(test.player().money, 100 - TowerType::Potato.cost());
+}
+
+#[test]
+fn upgrading_a_tower_raises_its_level() {
+    let (mut test, tower) = selected_tomato_tower();
+
+    tap_key(&mut test, KeyCode::U);
+
+    assert_eq!(test.get::<Tower>(tower).unwrap().level, 2);
+    assert_eq!(test.get::<Tower>(tower).unwrap().damage(), 2);
+    assert_eq!(test.player().money, 100 - TowerType::Tomato.upgrade_cost(1));
+}
+
+#[test]
+fn selling_a_tower_refunds_half_and_frees_the_slot() {
+    let (mut test, _) = selected_tomato_tower();
+    let bases = test.count::<With<TowerBase>>();
+
+    tap_key(&mut test, KeyCode::X);
+
+    assert_eq!(test.count::<With<Tower>>(), 0);
+    assert_eq!(test.count::<With<TowerBase>>(), bases + 1);
+    assert_eq!(test.player().money, 100 + TowerType::Tomato.cost() / 2);
+}
+
+#[test]
+fn targeting_mode_cycles() {
+    let (mut test, tower) = selected_tomato_tower();
+    assert_eq!(
+        *test.get::<TargetingMode>(tower).unwrap(),
+        TargetingMode::Closest
+    );
+
+    tap_key(&mut test, KeyCode::T);
+    assert_eq!(
+        *test.get::<TargetingMode>(tower).unwrap(),
+        TargetingMode::Strongest
+    );
+}
+
+#[test]
+fn towers_keep_their_own_combat_stats() {
+    let (mut test, tower) = selected_tomato_tower();
+
+    test.step_seconds(5.0);
+    let kills = test.get::<CombatStats>(tower).unwrap().kills;
+    test.app.world.send_event(TargetDeathEvent {
+        killed_by: Some(tower),
+    });
+    test.step(1);
+
+    let tower_stats = *test.get::<CombatStats>(tower).unwrap();
+    assert!(tower_stats.shots > 0);
+    assert!(tower_stats.kills > kills);
+    // The only tower of its type, so it did everything its type did
+    let stats = test.app.world.resource::<MatchStats>();
+    assert_eq!(stats.tower_type(TowerType::Tomato), tower_stats);
+}
+
+#[test]
+fn selected_towers_show_their_stats() {
+    let (mut test, tower) = selected_tomato_tower();
+    test.step(1);
+
+    let panel = test.entities::<With<TowerPanel>>();
+    assert_eq!(panel.len(), 1);
+    let lines = &test.get::<TowerPanel>(panel[0]).unwrap().lines;
+    assert_eq!(lines[0], LocalizedText::new("tower-tomato"));
+    assert!(lines.contains(&LocalizedText::new("tower-kills").with_arg("kills", 0)));
+    assert!(lines.contains(
+        &LocalizedText::new("tower-level")
+            .with_arg("level", 1)
+            .with_arg("max", MAX_TOWER_LEVEL)
+    ));
+    assert!(lines.contains(&LocalizedText::new("tower-targeting-closest")));
+
+    test.get_mut::<Selection>(tower)
+        .unwrap()
+        .set_selected(false);
+    test.step(1);
+    assert_eq!(test.count::<With<TowerPanel>>(), 0);
+}
+
+fn panel_button(test: &mut TestApp, button: TowerPanelButton) -> Entity {
+    test.entities::<With<TowerPanelButton>>()
+        .into_iter()
+        .find(|entity| test.get::<TowerPanelButton>(*entity) == Some(&button))
+        .expect("tower panel button missing")
+}
+
+#[test]
+fn tower_panel_buttons_act_on_the_selected_tower() {
+    let (mut test, tower) = selected_tomato_tower();
+    let bases = test.count::<With<TowerBase>>();
+
+    let upgrade = panel_button(&mut test, TowerPanelButton::Upgrade);
+    test.click(upgrade);
+    test.step(1);
+    assert_eq!(test.get::<Tower>(tower).unwrap().level, 2);
+    let panel = test.entities::<With<TowerPanel>>()[0];
+    assert!(test.get::<TowerPanel>(panel).unwrap().lines.contains(
+        &LocalizedText::new("tower-level")
+            .with_arg("level", 2)
+            .with_arg("max", MAX_TOWER_LEVEL)
+    ));
+
+    let targeting = panel_button(&mut test, TowerPanelButton::Targeting);
+    test.click(targeting);
+    test.step(1);
+    assert_eq!(
+        *test.get::<TargetingMode>(tower).unwrap(),
+        TargetingMode::Strongest
+    );
+
+    let money = test.player().money;
+    let refund = test.get::<Tower>(tower).unwrap().sell_value();
+    let sell = panel_button(&mut test, TowerPanelButton::Sell);
+    test.click(sell);
+    test.step(2);
+    assert_eq!(test.count::<With<Tower>>(), 0);
+    assert_eq!(test.count::<With<TowerBase>>(), bases + 1);
+    assert_eq!(test.player().money, money + refund);
+    assert_eq!(test.count::<With<TowerPanel>>(), 0);
+}
+
+#[test]
+fn tower_panel_buttons_act_on_every_selected_tower() {
+    let mut test = TestApp::with_level(Level::default(), "0=tomato,1=tomato".parse().unwrap());
+    let towers = test.entities::<With<Tower>>();
+    for tower in &towers {
+        test.select(*tower);
+    }
+    test.step(1);
+
+    let upgrade = panel_button(&mut test, TowerPanelButton::Upgrade);
+    test.click(upgrade);
+    test.step(1);
+
+    for tower in towers {
+        assert_eq!(test.get::<Tower>(tower).unwrap().level, 2);
+    }
+}
+
+#[test]
+fn fully_upgraded_towers_are_not_a_lack_of_money() {
+    let (mut test, tower) = selected_tomato_tower();
+    test.get_mut::<Tower>(tower).unwrap().level = MAX_TOWER_LEVEL;
+    test.take_sounds();
+
+    tap_key(&mut test, KeyCode::U);
+
+    assert_eq!(test.get::<Tower>(tower).unwrap().level, MAX_TOWER_LEVEL);
+    assert_eq!(test.player().money, 100);
+    assert!(!test.take_sounds().contains(&SoundCue::NotEnoughMoney));
+}
+
+#[test]
+fn next_wave_can_be_called_early() {
+    let mut level = Level::default();
+    level.waves.push(level.waves[0].clone());
+    let mut test = TestApp::with_level(level, TowerLayout::default());
+
+    tap_key(&mut test, KeyCode::N);
+
+    assert_eq!(test.count::<With<Target>>(), 48);
+    assert_eq!(test.app.world.resource::<WaveProgress>().next, 2);
+}
+
+#[test]
+fn calling_a_wave_as_the_countdown_ends_sends_one_wave() {
+    let mut level = Level::default();
+    level.waves.push(level.waves[0].clone());
+    level.waves.push(level.waves[0].clone());
+    let mut test = TestApp::with_level(level, TowerLayout::default());
+    for target in test.entities::<With<Target>>() {
+        test.app.world.despawn(target);
+    }
+    let mut progress = test.app.world.resource_mut::<WaveProgress>();
+    let almost_over = progress.countdown.duration() - Duration::from_millis(1);
+    progress.countdown.set_elapsed(almost_over);
+
+    tap_key(&mut test, KeyCode::N);
+
+    assert_eq!(test.count::<With<Target>>(), 24);
+    assert_eq!(test.app.world.resource::<WaveProgress>().next, 2);
+}
+
+/// Opens the controls screen through the pause and settings menus
+fn open_controls_screen() -> TestApp {
+    let mut test = TestApp::new();
+    tap_key(&mut test, KeyCode::Escape);
+
+    let settings = test.entities::<With<SettingsButton>>()[0];
+    test.click(settings);
+    test.step(1);
+    let controls = test.entities::<With<ControlsButton>>()[0];
+    test.click(controls);
+    test.step(1);
+    assert_eq!(test.state(), GameState::Controls);
+    test
+}
+
+fn rebind(test: &mut TestApp, action: GameAction, key: KeyCode) {
+    let button = test
+        .entities::<With<KeyBindingButton>>()
+        .into_iter()
+        .find(|&button| test.get::<KeyBindingButton>(button).unwrap().0 == action)
+        .unwrap();
+    test.click(button);
+    test.step(1);
+    tap_key(test, key);
+}
+
+#[test]
+fn actions_can_be_rebound() {
+    let mut test = open_controls_screen();
+
+    rebind(&mut test, GameAction::Sell, KeyCode::K);
+    assert_eq!(
+        test.app
+            .world
+            .resource::<KeyBindings>()
+            .key(GameAction::Sell),
+        Some(KeyCode::K)
+    );
+
+    // Only closes the controls screen, not the settings screen underneath
+    tap_key(&mut test, KeyCode::Escape);
+    assert_eq!(test.state(), GameState::Settings);
+}
+
+#[test]
+fn menus_close_with_the_rebound_pause_key() {
+    let mut test = open_controls_screen();
+    rebind(&mut test, GameAction::Pause, KeyCode::P);
+
+    tap_key(&mut test, KeyCode::Escape);
+    assert_eq!(test.state(), GameState::Controls);
+
+    tap_key(&mut test, KeyCode::P);
+    assert_eq!(test.state(), GameState::Settings);
+    tap_key(&mut test, KeyCode::P);
+    assert_eq!(test.state(), GameState::Paused);
+}
+
+#[test]
+fn keys_used_by_another_action_are_refused() {
+    let mut test = open_controls_screen();
+
+    rebind(&mut test, GameAction::Sell, KeyCode::U);
+
+    let bindings = test.app.world.resource::<KeyBindings>();
+    assert_eq!(bindings.key(GameAction::Sell), Some(KeyCode::X));
+    assert_eq!(bindings.key(GameAction::Upgrade), Some(KeyCode::U));
+    let status = test.entities::<With<ControlsStatusText>>()[0];
+    assert_eq!(
+        test.get::<LocalizedText>(status).unwrap().key,
+        "controls-conflict"
+    );
+}
+
+#[test]
+fn key_bindings_survive_a_restart() {
+    let path = std::env::temp_dir().join("towerdefense-key-bindings-test.ron");
+    let mut bindings = KeyBindings::default();
+    bindings.bind(GameAction::NextWave, KeyCode::Space).unwrap();
+    bindings.keys.remove(&GameAction::Pause);
+    bindings.save(&path).unwrap();
+
+    let loaded = KeyBindings::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.key(GameAction::NextWave), Some(KeyCode::Space));
+    // Actions the file doesn't mention get their default key
+    assert_eq!(loaded.key(GameAction::Pause), Some(KeyCode::Escape));
+}
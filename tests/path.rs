@@ -0,0 +1,163 @@
+mod common;
+
+use bevy::{math::Vec3Swizzles, prelude::*, render::mesh::VertexAttributeValues};
+use common::TestApp;
+use towerdefense::{
+    arrow_distance, components::Target, road_mesh, BuildGrid, Level, PathArrow, PathMarker,
+    PathRoad, PathSpline, TargetPath, TowerLayout,
+};
+
+fn bent_path() -> PathSpline {
+    PathSpline::new([
+        Vec2::ZERO,
+        Vec2::new(4.0, 0.0),
+        Vec2::new(4.0, 4.0),
+        Vec2::new(8.0, 4.0),
+    ])
+}
+
+#[test]
+fn spline_passes_through_its_points() {
+    let spline = bent_path();
+
+    assert_eq!(spline.point_at(0.0), Vec2::ZERO);
+    assert_eq!(spline.point_at(spline.length()), Vec2::new(8.0, 4.0));
+    // Rounding the corners is a little longer than the straight lines would be
+    assert!(spline.length() > 12.0);
+    assert!(spline.length() < 14.0);
+}
+
+#[test]
+fn equal_distances_are_equal_steps_along_the_curve() {
+    let spline = bent_path();
+    let step = spline.length() / 50.0;
+
+    let chords: Vec<f32> = (0..50)
+        .map(|i| {
+            let start = spline.point_at(step * i as f32);
+            start.distance(spline.point_at(step * (i + 1) as f32))
+        })
+        .collect();
+
+    // Straight chords cut the bends a little, but never by much
+    for chord in chords {
+        assert!((chord - step).abs() < step * 0.05, "{} vs {}", chord, step);
+    }
+}
+
+#[test]
+fn spline_carries_on_straight_past_its_ends() {
+    let spline = PathSpline::new([Vec2::ZERO, Vec2::new(0.0, 2.0), Vec2::new(2.0, 2.0)]);
+
+    assert_eq!(spline.tangent_at(0.0), Vec2::Y);
+    assert!(spline.point_at(-3.0).distance(Vec2::new(0.0, -3.0)) < 0.0001);
+    assert!(spline.tangent_at(spline.length()).distance(Vec2::X) < 0.0001);
+}
+
+#[test]
+fn distance_to_path_follows_the_curve() {
+    let level = Level {
+        spawn: Vec2::ZERO,
+        waypoints: vec![Vec2::new(0.0, 2.0), Vec2::new(2.0, 2.0)],
+        ..Level::default()
+    };
+
+    // Before the corner the curve swings out about 0.15 past the straight line
+    let outside_the_corner = Vec2::new(-1.15, 1.48);
+    assert!(level.distance_to_path(outside_the_corner) < 1.05);
+    assert!(level.distance_to_path(Vec2::ZERO) < 0.0001);
+}
+
+#[test]
+fn targets_face_along_the_path() {
+    let level = Level {
+        spawn: Vec2::ZERO,
+        waypoints: vec![Vec2::new(3.0, 0.0), Vec2::new(3.0, 3.0)],
+        ..Level::default()
+    };
+    let mut test = TestApp::with_level(level, TowerLayout::default());
+    test.step_seconds(8.0);
+
+    // The front of the line is out on the path by now
+    let (target, distance) = test
+        .entities::<With<Target>>()
+        .into_iter()
+        .map(|target| (target, test.get::<Target>(target).unwrap().distance))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap();
+    assert!(distance > 0.0);
+
+    let spline = &test.app.world.resource::<TargetPath>().spline;
+    let transform = test.get::<Transform>(target).unwrap();
+    assert!(
+        transform
+            .translation
+            .xz()
+            .distance(spline.point_at(distance))
+            < 0.0001
+    );
+    assert!(
+        transform
+            .forward()
+            .xz()
+            .distance(spline.tangent_at(distance))
+            < 0.0001
+    );
+}
+
+#[test]
+fn road_follows_the_path() {
+    let spline = bent_path();
+    let mesh = road_mesh(&spline, 1.0);
+
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("road mesh has no positions");
+    };
+    // Cross sections come in pairs, one on each side of the path and half the width away
+    for pair in positions.chunks(2) {
+        let (left, right) = (
+            Vec2::new(pair[0][0], pair[0][2]),
+            Vec2::new(pair[1][0], pair[1][2]),
+        );
+        assert!((left.distance(right) - 1.0).abs() < 0.0001);
+    }
+    let start = Vec2::new(positions[0][0], positions[0][2])
+        .lerp(Vec2::new(positions[1][0], positions[1][2]), 0.5);
+    assert!(start.distance(Vec2::ZERO) < 0.0001);
+}
+
+#[test]
+fn arrows_loop_back_to_the_spawn() {
+    assert_eq!(arrow_distance(1.0, 2.0, 10.0), 3.0);
+    assert_eq!(arrow_distance(9.0, 2.0, 10.0), 1.0);
+}
+
+#[test]
+fn path_is_shown_on_the_floor() {
+    let mut test = TestApp::new();
+
+    assert_eq!(test.count::<With<PathRoad>>(), 1);
+    assert_eq!(test.count::<With<PathMarker>>(), 2);
+    let arrow = test.entities::<With<PathArrow>>()[0];
+    let before = test.get::<Transform>(arrow).unwrap().translation;
+    test.step(10);
+    assert_ne!(test.get::<Transform>(arrow).unwrap().translation, before);
+}
+
+#[test]
+fn mazing_levels_only_mark_the_ends() {
+    let level = Level {
+        build_grid: Some(BuildGrid {
+            mazing: true,
+            ..BuildGrid::default()
+        }),
+        ..Level::default()
+    };
+    let test = TestApp::with_level(level, TowerLayout::default());
+
+    assert_eq!(test.count::<With<PathRoad>>(), 0);
+    assert_eq!(test.count::<With<PathArrow>>(), 0);
+    assert_eq!(test.count::<With<PathMarker>>(), 2);
+}